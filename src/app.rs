@@ -1,6 +1,11 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
 
 use anyhow::Error;
+use chrono::{DateTime, Local, Utc};
 use crossterm::cursor;
 use crossterm::KeyEvent;
 use crossterm::TerminalCursor;
@@ -8,13 +13,14 @@ use glob::glob;
 use path_abs::{PathDir, PathFile};
 use tui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Modifier, Style},
-    widgets::{Block, Borders, Paragraph, Tabs, Text, Widget},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Gauge, Paragraph, Tabs, Text, Widget},
 };
 
 use crate::{entry, utility, utility::config::CONFIG};
 use crate::entry::GooseberryEntryTrait;
 use crate::errors::Sorry;
+use crate::query::{self, EntryFilter, SortBy};
 
 //use tui::Terminal;
 //use unicode_width::UnicodeWidthStr;
@@ -22,38 +28,196 @@ use crate::errors::Sorry;
 
 /// Keyboard shortcuts in scrolling mode
 const HELP_TEXT: &str =
-    "< > : change tabs, ^ v : scroll\nn : new entry/resume editing, \
-     e <id>[Enter] : edit entry, a <id>[Enter] : archive entry\n\\t : toggle fold\nt <id>[Enter] : toggle Task\nq : quit";
+    "< > : change tabs, Shift-< > : move tab, ^ v : scroll, PgUp PgDn : scroll faster\nn : new entry/resume editing, N : discard draft and start a new entry\n\
+     e <id>[Enter] : edit entry, E <id>[Enter] : edit in $EDITOR, A <id>[Enter] : append to entry, a <id>[Enter] : archive entry, d <id>[Enter] : delete entry (asks first)\n\\t : cycle full/fold/compact view, \
+     z <id>[Enter] : collapse/expand entry, Z : collapse/expand all entries, o/O : sort by/direction, h : hide/show done Tasks, v : agenda view, / <tag>[Enter] : filter by tag, ? <text>[Enter] : search, Esc : clear filter\nt <id>[Enter] : toggle Task, y <id>[Enter] : copy entry id/path, l <id>[Enter] : reload entry from file, k/j <id>[Enter] : move entry up/down, g : go to today's Journal (or start it)\nc : cycle syntax theme, r : relative/absolute dates, i : quick capture to inbox, L : activity log, T : tags, + <t/r/j/e/h> : new entry of any type, q : quit";
 
 /// Keyboard shortcuts in writing mode
 const WRITING_HELP_TEXT: &str =
-    "Ctrl-n : next box, Ctrl-b : previous box\nCtrl-s : save, Esc : pause writing";
+    "Ctrl-n : next box, Ctrl-b : previous box, < > Home End : move cursor\nCtrl-s : save, Ctrl-w : save and keep writing, Esc : pause writing";
+
+/// Columns of the list box that entry text can't use (borders and a space before the right one)
+const LIST_BOX_PADDING: u16 = 3;
+
+/// Lines taken from the top of the list box for the Task tab's progress bar
+const PROGRESS_HEIGHT: u16 = 1;
 
 /// Percentage of the terminal to use for displaying the tab bar (on top)
 pub(crate) const TAB_BOX_PERCENT: u16 = 7;
 /// Percentage of the terminal to use for displaying the help text (at the bottom)
 pub(crate) const HELP_BOX_PERCENT: u16 = 13;
 
+/// Something that changed an entry during this session
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ActivityKind {
+    Added,
+    Edited,
+    Appended,
+    Deleted,
+    Archived,
+    Toggled,
+    Reloaded,
+    Moved,
+}
+
+/// One line of the session's activity log
+#[derive(Debug, Clone)]
+pub struct Activity {
+    pub time: DateTime<Utc>,
+    pub kind: ActivityKind,
+    pub entry_type: entry::GooseberryEntryType,
+    pub entry_id: u64,
+}
+
+impl fmt::Display for Activity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:?} {} {}",
+            self.time.with_timezone(&Local).format("%T"),
+            self.kind,
+            self.entry_type,
+            self.entry_id
+        )
+    }
+}
+
 /// Main application
 pub struct GooseberryTabs {
     /// list of `GooseberryTab`s
     pub tabs: Vec<GooseberryTab>,
     /// index of active tab
     pub index: usize,
+    /// true => changes are only kept in memory, nothing is written to the folder
+    pub dry_run: bool,
+    /// true => `q` was pressed with unsaved drafts and is waiting for a second `q`
+    confirming_quit: bool,
+    /// tabs of entry types left out of `CONFIG.tab_order`, loaded so new entries can still be written
+    hidden_tabs: Vec<GooseberryTab>,
+    /// tab to go back to after a quick capture in the inbox tab
+    capture_return_index: Option<usize>,
+    /// true => the capture is in a hidden tab that's shown (as the last tab) until writing stops
+    capture_in_hidden_tab: bool,
+    /// true => `+` was pressed and the next key picks the type of the new entry
+    picking_type: bool,
+    /// advisory lock on the entries folder, released when the app is dropped
+    lock: Option<utility::lock::FolderLock>,
+    /// what's been added/edited/deleted/toggled this session, oldest first
+    /// keeps at most `CONFIG.activity_log_length` lines
+    activity_log: VecDeque<Activity>,
+    /// true => the activity log is shown over the active tab
+    showing_log: bool,
+    /// Some(selected row) => the tag overview is shown over the active tab
+    showing_tags: Option<usize>,
+}
+
+/// Checks that `path` is a folder, making it (and its parents) if it's missing and `create` is set
+pub fn entries_folder(path: &Path, create: bool) -> Result<PathDir, Error> {
+    let missing = |reason: &str| Sorry::MissingFolder {
+        path: path.display().to_string(),
+        reason: reason.into(),
+    };
+    if path.exists() && !path.is_dir() {
+        Err(missing("it's a file, not a folder").into())
+    } else if path.exists() {
+        Ok(PathDir::new(path)?)
+    } else if create {
+        Ok(PathDir::create_all(path)?)
+    } else {
+        Err(missing("it doesn't exist").into())
+    }
 }
 
 impl GooseberryTabs {
+    /// Same as `from_folder`, but makes the folder first if it doesn't exist yet
+    /// (a dry run doesn't make anything, so there it has to exist already)
+    pub fn from_folder_or_create(path: &Path, dry_run: bool) -> Result<Self, Error> {
+        Self::from_folder(&entries_folder(path, !dry_run)?, dry_run)
+    }
+
     /// Retrieve all entries from a folder (expects <entry_type>_<entry_id>.md)
-    /// Make a tab for each kind of entry_type
-    pub fn from_folder(folder: &PathDir) -> Result<Self, Error> {
+    /// Make a tab for each kind of entry_type in `CONFIG.tab_order`
+    /// The other types are loaded into hidden tabs
+    /// The active tab and display modes are restored from the last session
+    /// Symlinks in the folder path are resolved if `CONFIG.resolve_symlinks` is set
+    /// Takes an advisory lock on the folder (except in a dry run), warns if someone else has it
+    /// Fails if the folder has files of unknown entry types or more than `CONFIG.max_entry_types` types
+    pub fn from_folder(folder: &PathDir, dry_run: bool) -> Result<Self, Error> {
+        let folder = if CONFIG.resolve_symlinks {
+            folder.canonicalize()?
+        } else {
+            folder.clone()
+        };
+        let type_names = entry::entry_type_names(&folder)?;
+        if type_names.len() > CONFIG.max_entry_types {
+            return Err(Sorry::TooManyEntryTypes {
+                max: CONFIG.max_entry_types,
+                types: type_names,
+            }
+            .into());
+        }
+        let unexpected_types = type_names
+            .into_iter()
+            .filter(|name| name.parse::<entry::GooseberryEntryType>().is_err())
+            .collect::<Vec<_>>();
+        if !unexpected_types.is_empty() {
+            return Err(Sorry::UnexpectedEntryTypes {
+                types: unexpected_types,
+            }
+            .into());
+        }
+        let mut tabs: Vec<GooseberryTab> = Vec::new();
+        for entry_type in &CONFIG.tab_order {
+            if tabs.iter().all(|t| t.entry_type != *entry_type) {
+                tabs.push(GooseberryTab::from_folder(*entry_type, &folder, dry_run)?);
+            }
+        }
+        if tabs.is_empty() {
+            return Err(Sorry::OutOfCheeseError {
+                message: "tab_order in the config needs at least one entry type".into(),
+            }
+            .into());
+        }
+        let mut hidden_tabs = Vec::new();
+        for entry_type in &entry::GooseberryEntryType::ALL {
+            if tabs.iter().all(|t| t.entry_type != *entry_type) {
+                hidden_tabs.push(GooseberryTab::from_folder(*entry_type, &folder, dry_run)?);
+            }
+        }
+        // pick up where the last session left off
+        let session = utility::session::Session::load();
+        for tab in &mut tabs {
+            if let Some(display_mode) = session.display_mode(tab.entry_type) {
+                tab.display_mode = display_mode;
+            }
+        }
+        let index = if session.active_tab < tabs.len() {
+            session.active_tab
+        } else {
+            0
+        };
+        let lock = if dry_run {
+            None
+        } else {
+            let (lock, warning) = utility::lock::FolderLock::acquire(&folder)?;
+            if let Some(warning) = warning {
+                tabs[0].add_message(warning);
+            }
+            Some(lock)
+        };
         Ok(Self {
-            tabs: vec![
-                GooseberryTab::from_folder(entry::GooseberryEntryType::Task, folder)?,
-                GooseberryTab::from_folder(entry::GooseberryEntryType::Journal, folder)?,
-                GooseberryTab::from_folder(entry::GooseberryEntryType::Research, folder)?,
-                GooseberryTab::from_folder(entry::GooseberryEntryType::Event, folder)?,
-            ],
-            index: 0,
+            tabs,
+            index,
+            dry_run,
+            confirming_quit: false,
+            hidden_tabs,
+            capture_return_index: None,
+            capture_in_hidden_tab: false,
+            picking_type: false,
+            lock,
+            activity_log: VecDeque::new(),
+            showing_log: false,
+            showing_tags: None,
         })
     }
 
@@ -62,15 +226,252 @@ impl GooseberryTabs {
         let titles = self
             .tabs
             .iter()
-            .map(|t| t.title.clone())
+            .map(|t| t.title_with_count())
             .collect::<Vec<_>>();
+        let banner = if self.dry_run {
+            "DRY RUN - changes won't be saved"
+        } else if self.lock.as_ref().is_some_and(|l| !l.is_owned()) {
+            "SHARED FOLDER - another gooseberry is using it"
+        } else {
+            ""
+        };
         let mut tabs = Tabs::default()
-            .block(Block::default().borders(Borders::ALL))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(banner)
+                    .title_style(Style::default().modifier(Modifier::BOLD)),
+            )
             .titles(&titles)
             .select(self.index)
             .style(Style::default().fg(CONFIG.tab_inactive_color))
             .highlight_style(Style::default().fg(CONFIG.tab_active_color));
         self.tabs[self.index].render(frame, &mut tabs);
+        if self.showing_log {
+            self.render_activity_log(frame);
+        }
+        if let Some(selected) = self.showing_tags {
+            self.render_tags(frame, selected);
+        }
+    }
+
+    /// Tags of all entries (shown or not, in every tab) with how many entries have each one
+    fn tag_counts(&self) -> Vec<(String, usize)> {
+        query::tag_counts(
+            self.tabs
+                .iter()
+                .chain(self.hidden_tabs.iter())
+                .flat_map(|tab| tab.iter_entries()),
+        )
+    }
+
+    /// Shows every tag with its count in a box over the middle of the screen, the selected one highlighted
+    /// Scrolls so the selected tag is always in the box
+    fn render_tags(&self, frame: &mut utility::interactive::TuiFrame, selected: usize) {
+        let area = utility::interactive::centered_rect(70, 60, frame.size());
+        let tag_counts = self.tag_counts();
+        let lines = if tag_counts.is_empty() {
+            vec![String::from("No tags yet")]
+        } else {
+            tag_counts
+                .iter()
+                .map(|(tag, count)| format!("{:>5}  {}", count, tag))
+                .collect()
+        };
+        let height = area.height.saturating_sub(2) as usize;
+        let start = (selected + 1).saturating_sub(height);
+        let texts = utility::interactive::fill_area(&lines[start.min(lines.len())..], area)
+            .into_iter()
+            .enumerate()
+            .map(|(row, line)| {
+                if start + row == selected && !tag_counts.is_empty() {
+                    Text::styled(line, Style::default().modifier(Modifier::REVERSED))
+                } else {
+                    Text::Raw(line.into())
+                }
+            })
+            .collect::<Vec<_>>();
+        Paragraph::new(texts.iter())
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Tags (^ v : select, Enter : filter by tag, any other key : close)")
+                    .title_style(Style::default().modifier(Modifier::BOLD)),
+            )
+            .render(frame, area);
+    }
+
+    /// Filters every tab by `tag` (keeping any search), and goes to the first tab with matches
+    /// if the active one has none
+    fn filter_all_by_tag(&mut self, tag: &str) {
+        for tab in self.tabs.iter_mut().chain(self.hidden_tabs.iter_mut()) {
+            tab.apply_filter(EntryFilter {
+                tag: Some(tag.to_owned()),
+                ..tab.filter.clone()
+            });
+        }
+        if self.tabs[self.index].visible_ids.is_empty() {
+            if let Some(index) = self.tabs.iter().position(|t| !t.visible_ids.is_empty()) {
+                self.index = index;
+            }
+        }
+        self.tabs[self.index].message = Some(format!("Filtered every tab by {}", tag));
+    }
+
+    /// Shows the activity log (newest first) in a box over the middle of the screen
+    fn render_activity_log(&self, frame: &mut utility::interactive::TuiFrame) {
+        let area = utility::interactive::centered_rect(70, 60, frame.size());
+        let lines = if self.activity_log.is_empty() {
+            vec![String::from("Nothing's changed yet")]
+        } else {
+            self.activity_log
+                .iter()
+                .rev()
+                .map(|activity| activity.to_string())
+                .collect()
+        };
+        let texts = utility::interactive::fill_area(&lines, area)
+            .into_iter()
+            .map(|line| Text::Raw(line.into()))
+            .collect::<Vec<_>>();
+        Paragraph::new(texts.iter())
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Activity (any key : close)")
+                    .title_style(Style::default().modifier(Modifier::BOLD)),
+            )
+            .render(frame, area);
+    }
+
+    /// Moves what the tabs did into the activity log, dropping the oldest lines past the limit
+    fn collect_activity(&mut self) {
+        for tab in self.tabs.iter_mut().chain(self.hidden_tabs.iter_mut()) {
+            self.activity_log.extend(tab.activity.drain(..));
+        }
+        while self.activity_log.len() > CONFIG.activity_log_length {
+            self.activity_log.pop_front();
+        }
+    }
+
+    /// The active tab and how each tab is shown, to restore next time
+    pub fn session(&self) -> utility::session::Session {
+        utility::session::Session {
+            active_tab: self.capture_return_index.unwrap_or(self.index),
+            tabs: self
+                .tabs
+                .iter()
+                .chain(self.hidden_tabs.iter())
+                .map(|t| utility::session::TabState {
+                    entry_type: t.entry_type,
+                    display_mode: t.display_mode,
+                })
+                .collect(),
+        }
+    }
+
+    /// Where the terminal's cursor should be, None => hidden
+    pub fn cursor_position(&self, terminal_size: Rect) -> Option<(u16, u16)> {
+        if self.showing_log || self.showing_tags.is_some() {
+            None
+        } else {
+            self.tabs[self.index].cursor_position(terminal_size)
+        }
+    }
+
+    /// Glob patterns for the entry files of every tab (hidden ones too), to watch for changes
+    pub fn watch_patterns(&self) -> Vec<String> {
+        self.tabs
+            .iter()
+            .chain(self.hidden_tabs.iter())
+            .map(|tab| tab.entry_type.glob_pattern(&tab.folder))
+            .collect()
+    }
+
+    /// Reloads the entry whose file changed outside gooseberry (added, edited or removed)
+    /// Files that still match what's in memory (e.g. after saving) are left alone
+    /// If the file can't be read the entry stays as it was and the active tab says why
+    pub fn file_changed(&mut self, path: &Path) {
+        let tab = self
+            .tabs
+            .iter_mut()
+            .chain(self.hidden_tabs.iter_mut())
+            .find_map(|tab| {
+                tab.entry_type
+                    .id_from_file(&tab.folder, path)
+                    .map(|id| (tab, id))
+            });
+        let (tab, id) = match tab {
+            Some(tab_id) => tab_id,
+            None => return,
+        };
+        let unchanged = match (tab.entries.get(&id), fs::read_to_string(path)) {
+            (Some(g_entry), Ok(content)) => g_entry.to_markdown_string() == content,
+            _ => false,
+        };
+        if unchanged {
+            return;
+        }
+        if let Err(error) = tab.reload_entry(id) {
+            warn!("Couldn't reload {}: {}", path.display(), error);
+            let message = format!("Couldn't reload {} {}: {}", tab.entry_type, id, error);
+            self.tabs[self.index].message = Some(message);
+        }
+        self.collect_activity();
+    }
+
+    /// The file of the entry to open in an editor, if `E <id>` was just used in the active tab
+    pub fn editor_file(&self) -> Option<PathBuf> {
+        let tab = &self.tabs[self.index];
+        tab.editor_entry
+            .map(|id| tab.entry_type.file_path(&tab.folder, id))
+    }
+
+    /// Picks up the changes after the editor from `editor_file` closes
+    pub fn finish_editor(&mut self, edited: Result<(), Error>) {
+        self.tabs[self.index].finish_editor(edited);
+        self.collect_activity();
+    }
+
+    /// All entries (across tabs) that match a filter, sorted
+    /// Ignores what's currently visible in each tab
+    pub fn query(&self, filter: &EntryFilter, sort_by: SortBy) -> Vec<&entry::GooseberryEntry> {
+        let mut entries = self
+            .tabs
+            .iter()
+            .chain(self.hidden_tabs.iter())
+            .flat_map(|t| t.iter_entries())
+            .filter(|e| filter.matches(e))
+            .collect::<Vec<_>>();
+        query::sort_entries(&mut entries, sort_by, |entry_type| {
+            self.tabs
+                .iter()
+                .chain(self.hidden_tabs.iter())
+                .find(|t| t.entry_type == entry_type)
+                .map(|t| &t.folder)
+        });
+        entries
+    }
+
+    /// e.g. "gooseberry - Task (3/10)" for 3 shown out of 10 Task entries
+    pub fn window_title(&self) -> String {
+        let tab = &self.tabs[self.index];
+        format!(
+            "gooseberry - {} ({}/{})",
+            tab.title,
+            tab.visible_ids.len(),
+            tab.entries.len()
+        )
+    }
+
+    /// Entry types of the tabs, from left to right (without a hidden tab shown for writing)
+    pub fn tab_order(&self) -> Vec<entry::GooseberryEntryType> {
+        let shown = if self.capture_in_hidden_tab {
+            self.tabs.len() - 1
+        } else {
+            self.tabs.len()
+        };
+        self.tabs[..shown].iter().map(|t| t.entry_type).collect()
     }
 
     /// Checks if the active tab is in writing mode
@@ -78,24 +479,169 @@ impl GooseberryTabs {
         self.tabs[self.index].is_writing
     }
 
+    /// Titles of tabs with unsaved content in their input boxes
+    pub fn unsaved_tabs(&self) -> Vec<String> {
+        self.tabs
+            .iter()
+            .chain(self.hidden_tabs.iter())
+            .filter(|t| t.has_unsaved_draft())
+            .map(|t| t.title.clone())
+            .collect()
+    }
+
     /// Handle keyboard input events
     /// left and right arrow keys change the active tab
     /// `q` in scrolling mode returns true (to exit the app)
+    ///     if any tab has an unsaved draft, `q` has to be pressed twice and any other key cancels
+    /// `c` and `r` switch the syntax theme and relative/absolute dates for all tabs
+    /// `i` starts a quick capture in the inbox tab, `+ <t/r/j/e/h>` starts an entry of any type
+    ///     (even ones without a tab), `+ [Enter]` one of `CONFIG.default_entry_type` if it's set
+    /// `L` shows the activity log, any key closes it
+    /// `T` shows every tag with its count, Enter filters all tabs by the selected one
     /// Everything else is handled by the active tab's keypress function
     pub fn keypress(&mut self, terminal_size: Rect, key: KeyEvent) -> Result<bool, Error> {
-        if !self.is_writing() {
+        let should_quit = self.handle_key(terminal_size, key);
+        self.collect_activity();
+        should_quit
+    }
+
+    fn handle_key(&mut self, terminal_size: Rect, key: KeyEvent) -> Result<bool, Error> {
+        if self.showing_log {
+            self.showing_log = false;
+            return Ok(false);
+        }
+        if let Some(selected) = self.showing_tags.take() {
+            let tag_counts = self.tag_counts();
+            match key {
+                KeyEvent::Up => self.showing_tags = Some(selected.saturating_sub(1)),
+                KeyEvent::Down => {
+                    self.showing_tags = Some((selected + 1).min(tag_counts.len().saturating_sub(1)))
+                }
+                KeyEvent::Char('\n') => {
+                    if let Some((tag, _)) = tag_counts.get(selected) {
+                        self.filter_all_by_tag(tag);
+                    }
+                }
+                _ => (),
+            }
+            return Ok(false);
+        }
+        if self.confirming_quit {
+            self.confirming_quit = false;
+            self.tabs[self.index].message = None;
+            return Ok(key == KeyEvent::Char('q'));
+        }
+        if self.picking_type {
+            self.picking_type = false;
+            self.tabs[self.index].message = None;
+            let entry_type = match key {
+                KeyEvent::Char('\n') => CONFIG.default_entry_type,
+                KeyEvent::Char(c) => entry::GooseberryEntryType::ALL
+                    .iter()
+                    .find(|t| t.to_string().to_lowercase().starts_with(c))
+                    .cloned(),
+                _ => None,
+            };
+            return match entry_type {
+                Some(entry_type) => self.capture(entry_type, terminal_size).map(|_| false),
+                None => {
+                    self.tabs[self.index].message = Some("No such entry type, cancelled".into());
+                    Ok(false)
+                }
+            };
+        }
+        if self.tabs[self.index].is_typing() || self.tabs[self.index].is_confirming() {
+            self.tabs[self.index].keypress(terminal_size, key)?;
+        } else if !self.is_writing() {
             match key {
-                KeyEvent::Char('q') => return Ok(true),
+                KeyEvent::Char('q') => {
+                    let unsaved_tabs = self.unsaved_tabs();
+                    if unsaved_tabs.is_empty() {
+                        return Ok(true);
+                    }
+                    self.confirming_quit = true;
+                    self.tabs[self.index].message = Some(format!(
+                        "Unsaved drafts in {}! q : quit anyway, any other key : cancel",
+                        unsaved_tabs.join(", ")
+                    ));
+                }
                 KeyEvent::Right => self.next(),
                 KeyEvent::Left => self.previous(),
+                KeyEvent::ShiftRight => self.move_right(),
+                KeyEvent::ShiftLeft => self.move_left(),
+                KeyEvent::Char('c') => {
+                    let theme = utility::formatting::cycle_theme();
+                    self.tabs[self.index].message = Some(format!("Syntax theme: {}", theme));
+                }
+                KeyEvent::Char('r') => {
+                    let message = if utility::formatting::toggle_relative_dates() {
+                        "Showing relative dates"
+                    } else {
+                        "Showing absolute dates"
+                    };
+                    self.tabs[self.index].message = Some(message.into());
+                }
+                KeyEvent::Char('i') => self.capture(CONFIG.inbox_type, terminal_size)?,
+                KeyEvent::Char('L') => self.showing_log = true,
+                KeyEvent::Char('T') => self.showing_tags = Some(0),
+                KeyEvent::Char('+') => {
+                    self.picking_type = true;
+                    let default = CONFIG
+                        .default_entry_type
+                        .map(|entry_type| format!(", [Enter] : {}", entry_type))
+                        .unwrap_or_default();
+                    self.tabs[self.index].message = Some(format!(
+                        "New entry of type? t : Task, r : Research, j : Journal, e : Event, h : Habit{}",
+                        default
+                    ));
+                }
                 _key => self.tabs[self.index].keypress(terminal_size, _key)?,
             }
         } else {
             self.tabs[self.index].keypress(terminal_size, key)?;
+            if !self.is_writing() {
+                if let Some(index) = self.capture_return_index.take() {
+                    if self.capture_in_hidden_tab {
+                        self.capture_in_hidden_tab = false;
+                        if let Some(tab) = self.tabs.pop() {
+                            self.hidden_tabs.push(tab);
+                        }
+                    }
+                    self.index = index;
+                }
+            }
         }
         Ok(false)
     }
 
+    /// Starts writing a new entry of a type, going back to the current tab when done
+    /// Types without a tab get their hidden tab shown at the end until writing stops
+    fn capture(
+        &mut self,
+        entry_type: entry::GooseberryEntryType,
+        terminal_size: Rect,
+    ) -> Result<(), Error> {
+        let return_index = self.index;
+        if let Some(index) = self.tabs.iter().position(|t| t.entry_type == entry_type) {
+            self.index = index;
+        } else if let Some(index) = self
+            .hidden_tabs
+            .iter()
+            .position(|t| t.entry_type == entry_type)
+        {
+            self.tabs.push(self.hidden_tabs.remove(index));
+            self.capture_in_hidden_tab = true;
+            self.index = self.tabs.len() - 1;
+        } else {
+            return Err(Sorry::OutOfCheeseError {
+                message: format!("There's no tab at all for {}", entry_type),
+            }
+            .into());
+        }
+        self.capture_return_index = Some(return_index);
+        self.tabs[self.index].start_writing(terminal_size)
+    }
+
     fn next(&mut self) {
         self.index = (self.index + 1) % self.tabs.len();
     }
@@ -107,6 +653,20 @@ impl GooseberryTabs {
             self.index = self.tabs.len() - 1;
         }
     }
+
+    /// Swaps the active tab with the one on its right (wraps around), it stays active
+    fn move_right(&mut self) {
+        let old_index = self.index;
+        self.next();
+        self.tabs.swap(old_index, self.index);
+    }
+
+    /// Swaps the active tab with the one on its left (wraps around), it stays active
+    fn move_left(&mut self) {
+        let old_index = self.index;
+        self.previous();
+        self.tabs.swap(old_index, self.index);
+    }
 }
 
 /// Tab for displaying and editing a list of entries
@@ -116,12 +676,24 @@ pub struct GooseberryTab {
     title: String,
     /// type of entries listed
     entry_type: entry::GooseberryEntryType,
-    /// true => hides the longer descriptions
-    fold: bool,
+    /// how much of each entry to show
+    display_mode: utility::formatting::DisplayMode,
+    /// what the entries are sorted by, and which way round
+    list_order: query::ListOrder,
+    /// true => done Tasks aren't shown (whatever the filter says)
+    hide_done: bool,
+    /// true => Events/Tasks with due dates are listed by day (Event and Task tabs only)
+    agenda: bool,
+    /// ids of entries whose descriptions are hidden in Full mode
+    collapsed_ids: HashSet<u64>,
     /// dict of entry_id: entry
     entries: HashMap<u64, entry::GooseberryEntry>,
-    /// which ids to display (TODO: use this when you add filtering options)
+    /// which ids to display (the ones matching `filter`)
     visible_ids: Vec<u64>,
+    /// which entries are shown, empty => all of them
+    filter: EntryFilter,
+    /// ids of entries whose last save failed, so the file is out of date
+    unsaved_ids: HashSet<u64>,
     /// true if Tab is in writing mode
     is_writing: bool,
     /// struct of text input boxes used in writing mode
@@ -138,9 +710,26 @@ pub struct GooseberryTab {
     picking_entry: bool,
     /// Entry ID entered
     selected_entry: u64,
+    /// key that started a line of text entry (`/` for a tag filter, `?` for a search)
+    /// and what's been typed so far
+    typing: Option<(char, String)>,
+    /// ID of the entry waiting for a y/n before it's deleted
+    deleting: Option<u64>,
+    /// ID of the entry to open in an external editor, main runs the editor and calls `finish_editor`
+    editor_entry: Option<u64>,
     /// if editing an entry, this stores the old entry (TODO: add as a field to the `picking_char` enum)
     editing_entry: Option<entry::GooseberryEntry>,
+    /// true => the input box holds text to add to the end of `editing_entry`
+    appending: bool,
     cursor: TerminalCursor,
+    /// feedback for the last action, shown in the help box title until the next keypress
+    message: Option<String>,
+    /// why the last save from writing mode failed, shown until everything is saved
+    save_error: Option<String>,
+    /// changes since `GooseberryTabs` last collected them for the activity log
+    activity: Vec<Activity>,
+    /// true => entries are never written to or removed from `folder`
+    dry_run: bool,
 }
 
 //fn get_cursor(x: u16, y: u16) -> Result<(), Error> {
@@ -153,36 +742,125 @@ impl GooseberryTab {
     pub fn from_folder(
         entry_type: entry::GooseberryEntryType,
         folder: &PathDir,
+        dry_run: bool,
     ) -> Result<Self, Error> {
         let mut entries = HashMap::new();
         let mut visible_ids = Vec::new();
-        for file in glob(&format!(
-            "{}/{}_*.md",
-            folder.as_path().display(),
-            entry_type
-        ))? {
-            let g_entry = entry::GooseberryEntry::from_file(&PathFile::new(file?)?)?;
-
+        let mut file_ids = Vec::new();
+        let mut skipped_files = Vec::new();
+        let mut unreadable_files = Vec::new();
+        for file in glob(&entry_type.glob_pattern(folder))? {
+            let file = file?;
+            // Only files named with a numeric ID are entries, and the header has to agree
+            // (on the type too, e.g. a file without a `Type:` read as `CONFIG.default_entry_type`)
+            let file_id = match entry_type.id_from_file(folder, &file) {
+                Some(id) => id,
+                None => {
+                    skipped_files.push(file);
+                    continue;
+                }
+            };
+            // so a new entry doesn't overwrite a file that's there but can't be read
+            file_ids.push(file_id);
+            let g_entry = match entry::GooseberryEntry::from_file(&PathFile::new(&file)?) {
+                Ok(g_entry) => g_entry,
+                Err(error) => {
+                    unreadable_files.push((file, error));
+                    continue;
+                }
+            };
+            if g_entry.id() != file_id || g_entry.entry_type() != entry_type {
+                skipped_files.push(file);
+                continue;
+            }
             visible_ids.push(g_entry.id());
             entries.insert(g_entry.id(), g_entry);
         }
-        let next_id = *visible_ids.iter().max().unwrap_or(&0) + 1;
+        info!(
+            "Loaded {} {} entries from {}",
+            entries.len(),
+            entry_type,
+            folder.as_path().display()
+        );
+        for file in &skipped_files {
+            warn!("Skipped {}", file.display());
+        }
+        for (file, error) in &unreadable_files {
+            warn!("Couldn't read {}: {}", file.display(), error);
+        }
+        let mut messages = Vec::new();
+        if !skipped_files.is_empty() {
+            messages.push(format!(
+                "Skipped {} file(s) without a matching ID in the name: {}",
+                skipped_files.len(),
+                skipped_files
+                    .iter()
+                    .filter_map(|f| f.file_name())
+                    .map(|f| f.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        if !unreadable_files.is_empty() {
+            messages.push(format!(
+                "Couldn't read {} file(s), gooseberry lint says why: {}",
+                unreadable_files.len(),
+                unreadable_files
+                    .iter()
+                    .filter_map(|(f, _)| f.file_name())
+                    .map(|f| f.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        let message = if messages.is_empty() {
+            None
+        } else {
+            Some(messages.join("; "))
+        };
+        let next_id = entry_type.next_id(folder, &file_ids);
+        let mut input_boxes = entry_type.get_input_boxes();
+        let draft_path = entry_type.draft_path(folder);
+        let message = if draft_path.exists() {
+            input_boxes.load_draft(&PathFile::new(&draft_path)?.read_string()?);
+            let restored = String::from("Restored an unfinished draft, n : resume");
+            Some(match message {
+                Some(message) => format!("{}; {}", message, restored),
+                None => restored,
+            })
+        } else {
+            message
+        };
         Ok(GooseberryTab {
             title: format!("{}", entry_type),
             entries,
-            fold: false,
+            display_mode: utility::formatting::DisplayMode::Full,
+            list_order: query::ListOrder::default(),
+            hide_done: false,
+            agenda: false,
+            collapsed_ids: HashSet::new(),
             visible_ids,
+            filter: EntryFilter::default(),
+            unsaved_ids: HashSet::new(),
             is_writing: false,
-            input_boxes: entry_type.get_input_boxes(),
+            input_boxes,
             next_id,
             folder: folder.to_owned(),
             entry_type,
             scroll: 0,
             selected_entry: 0,
+            typing: None,
+            deleting: None,
+            editor_entry: None,
             editing_entry: None,
+            appending: false,
             picking_entry: false,
             picking_char: None,
             cursor: cursor(),
+            message,
+            save_error: None,
+            activity: Vec::new(),
+            dry_run,
         })
     }
 
@@ -197,51 +875,163 @@ impl GooseberryTab {
                 Constraint::Percentage(HELP_BOX_PERCENT),
             ]
         };
-        Layout::default()
+        let mut chunks = Layout::default()
             .direction(Direction::Vertical)
             .margin(1)
             .constraints(constraints.as_ref())
-            .split(terminal_size)
+            .split(terminal_size);
+        // the Task tab's progress bar goes above the entry list
+        if self.entry_type == entry::GooseberryEntryType::Task {
+            let list_box = &mut chunks[1];
+            let height = PROGRESS_HEIGHT.min(list_box.height);
+            list_box.y += height;
+            list_box.height -= height;
+        }
+        // the entry list is narrowed to `CONFIG.max_content_width` (plus borders)
+        if let Some(max_width) = CONFIG.max_content_width {
+            let list_box = &mut chunks[1];
+            let width = max_width.saturating_add(LIST_BOX_PADDING);
+            if list_box.width > width {
+                if CONFIG.center_content {
+                    list_box.x += (list_box.width - width) / 2;
+                }
+                list_box.width = width;
+            }
+        }
+        chunks
+    }
+
+    /// Which entries are listed and how, for formatting them
+    fn list_view(&self) -> entry::ListView<'_> {
+        entry::ListView {
+            visible_ids: &self.visible_ids,
+            unsaved_ids: &self.unsaved_ids,
+            collapsed_ids: &self.collapsed_ids,
+            display_mode: self.display_mode,
+            list_order: self.list_order,
+            agenda: self.agenda,
+        }
+    }
+
+    /// Width the entries are formatted to, what's left of the list box after borders
+    fn list_width(&self, terminal_size: Rect) -> u16 {
+        self.get_layout(terminal_size)[1]
+            .width
+            .saturating_sub(LIST_BOX_PADDING)
+    }
+
+    /// What's been typed so far in ID entry mode or text entry mode, e.g. `e 12` or `/ work`
+    fn picking_prompt(&self) -> Option<String> {
+        if let Some((c, text)) = &self.typing {
+            return Some(format!("{} {}", c, text));
+        }
+        if let Some(id) = self.deleting {
+            let title = self
+                .entries
+                .get(&id)
+                .map(|g_entry| g_entry.title())
+                .unwrap_or_default();
+            return Some(format!("Delete {} {} {:?}? y/n", self.entry_type, id, title));
+        }
+        match self.picking_char {
+            Some(c) if self.picking_entry => {
+                if self.selected_entry > 0 {
+                    Some(format!("{} {}", c, self.selected_entry))
+                } else {
+                    Some(format!("{} ", c))
+                }
+            }
+            _ => None,
+        }
     }
 
     /// Renders the help box at the bottom with the keyboard shortcuts
     /// Changes depending on the mode
-    /// TODO: Add a small box here which displays what's being typed during ID entry mode
+    /// The title shows the ID being typed in ID entry mode, otherwise the last message
+    /// A failed save is shown in red in front of that until it's fixed
     fn render_help_box(&self, frame: &mut utility::interactive::TuiFrame, chunk: Rect) {
+        let message = self
+            .picking_prompt()
+            .or_else(|| self.message.clone())
+            .unwrap_or_default();
+        let (message, style) = match &self.save_error {
+            Some(error) if message.is_empty() => (
+                format!("{} (w : retry)", error),
+                Style::default().fg(Color::Red).modifier(Modifier::BOLD),
+            ),
+            Some(error) => (
+                format!("{} (w : retry) | {}", error, message),
+                Style::default().fg(Color::Red).modifier(Modifier::BOLD),
+            ),
+            None => (message, Style::default().modifier(Modifier::BOLD)),
+        };
         let block = Block::default()
             .borders(Borders::ALL)
-            .title_style(Style::default().modifier(Modifier::BOLD));
+            .title(&message)
+            .title_style(style);
         let text = if self.is_writing {
             WRITING_HELP_TEXT
         } else {
             HELP_TEXT
         };
-        Paragraph::new(vec![Text::Raw(text.into())].iter())
+        Paragraph::new([Text::Raw(text.into())].iter())
             .block(block)
             .alignment(Alignment::Center)
             .wrap(true)
             .render(frame, chunk)
     }
 
+    /// How many Tasks are done, and how many there are
+    fn task_progress(&self) -> (usize, usize) {
+        let done = self.entries.values().filter(|e| e.is_done()).count();
+        (done, self.entries.len())
+    }
+
+    /// `done/total done` over a bar filled as far as the Tasks are done
+    fn render_progress(&self, frame: &mut utility::interactive::TuiFrame, chunk: Rect) {
+        let (done, total) = self.task_progress();
+        let label = format!("{}/{} done", done, total);
+        let percent = (done * 100).checked_div(total).unwrap_or(0);
+        Gauge::default()
+            .style(Style::default().fg(CONFIG.secondary_metadata_color))
+            .percent(percent as u16)
+            .label(&label)
+            .render(frame, chunk);
+    }
+
     /// Renders the active tab
     /// Tab bar
+    /// Task progress bar (Task tab only)
     /// List of entries
     /// Help box
     /// if in writing mode then displays text input boxes
     pub fn render(&self, frame: &mut utility::interactive::TuiFrame, tabs: &mut Tabs<String>) {
         let chunks = self.get_layout(frame.size());
         tabs.render(frame, chunks[0]);
-        Paragraph::new(
-            entry::GooseberryEntry::entries_to_styled_texts_same_type(
-                &self.entries,
-                &self.visible_ids,
-                self.fold,
-                frame.size().width - 5,
-            )
-                .unwrap()
-                .iter(),
+        if self.entry_type == entry::GooseberryEntryType::Task {
+            let height = PROGRESS_HEIGHT.min(chunks[1].y.saturating_sub(chunks[0].bottom()));
+            let progress = Rect::new(chunks[0].x, chunks[1].y - height, chunks[0].width, height);
+            self.render_progress(frame, progress);
+        }
+        let styled_blocks = entry::GooseberryEntry::entries_to_styled_blocks_same_type(
+            &self.entries,
+            self.list_view(),
+            self.list_width(frame.size()),
         )
-            .block(Block::default().borders(Borders::ALL))
+            .unwrap();
+        let title = self.list_title(&styled_blocks, chunks[1].width.saturating_sub(2));
+        let mut texts = styled_blocks
+            .into_iter()
+            .flat_map(|(_, texts)| texts.into_iter())
+            .collect::<Vec<_>>();
+        if texts.is_empty() {
+            texts.push(Text::Raw(self.empty_message().into()));
+        }
+        if let Some(query) = &self.filter.text {
+            texts = utility::formatting::highlight_matches(texts, query);
+        }
+        Paragraph::new(texts.iter())
+            .block(Block::default().borders(Borders::ALL).title(&title))
             .alignment(Alignment::Left)
             .scroll(self.scroll)
             .wrap(true)
@@ -252,113 +1042,671 @@ impl GooseberryTab {
         self.render_help_box(frame, chunks[chunks.len() - 1]);
     }
 
-    /// Called when user inputs `t <id>[Enter]` in the Task tab
-    /// toggles the state of a Task entry (done/not done)
-    /// TODO: Restrict this to Task Tab
-    fn toggle_task_entry(&mut self) -> Result<(), Error> {
-        if self.entry_type == entry::GooseberryEntryType::Task {
-            let t_entry =
-                self.entries
-                    .get_mut(&self.selected_entry)
-                    .ok_or(Sorry::MissingEntryID {
-                        entry_type: self.entry_type,
-                        entry_id: self.selected_entry,
-                    })?;
-            if let entry::GooseberryEntry::Task(ref mut t) = t_entry {
-                t.toggle();
+    /// Where typing goes in the active input box when writing, None otherwise
+    pub fn cursor_position(&self, terminal_size: Rect) -> Option<(u16, u16)> {
+        if !self.is_writing {
+            return None;
+        }
+        let chunks = self.get_layout(terminal_size);
+        self.input_boxes.cursor_position(&chunks[2..chunks.len() - 1])
+    }
+
+    /// All entries in the tab (in no particular order), whether or not they're currently shown
+    pub fn iter_entries(&self) -> impl Iterator<Item = &entry::GooseberryEntry> {
+        self.entries.values()
+    }
+
+    /// Gets an entry by ID, whether or not it's currently shown
+    pub fn get_entry(&self, id: u64) -> Option<&entry::GooseberryEntry> {
+        self.entries.get(&id)
+    }
+
+    /// The entry list as it's shown in the TUI, for printing to a terminal
+    /// `color` => with ANSI colors
+    pub fn to_ansi(&self, terminal_width: u16, color: bool) -> Result<String, Error> {
+        if self.visible_ids.is_empty() {
+            return Ok(String::new());
+        }
+        let texts = entry::GooseberryEntry::entries_to_styled_texts_same_type(
+            &self.entries,
+            self.list_view(),
+            terminal_width,
+        )?;
+        Ok(utility::formatting::texts_to_ansi(&texts, color))
+    }
+
+    /// Which entry is at the top of the list, and how many hidden entries there are
+    /// `width` is what the list wraps to, inside the borders
+    fn list_title(&self, styled_blocks: &[(Option<u64>, Vec<Text>)], width: u16) -> String {
+        let position = self.scroll_position(styled_blocks, width);
+        let hidden = self.hidden_indicator();
+        match (position, hidden) {
+            (Some((index, total)), Some(hidden)) => {
+                format!("Entry {} of {} - {}", index, total, hidden)
             }
-            self.save_entry(self.selected_entry)?;
+            (Some((index, total)), None) => format!("Entry {} of {}", index, total),
+            (None, Some(hidden)) => hidden,
+            (None, None) => String::new(),
         }
-        Ok(())
     }
 
-    /// Handles keyboard input
-    /// in scrolling mode:
-    ///     ^ v: scrolls
-    ///     n: starts/resumes writing mode
-    ///     `\t`: toggles folding
-    ///     e/t/d: starts ID entry mode
-    ///     0-9: if in ID entry mode, adds the digit to `self.selected_entry`
-    ///     `\n`: stops ID entry mode and executes e/t/d
-    pub fn keypress(&mut self, terminal_size: Rect, key: KeyEvent) -> Result<(), Error> {
-        if self.is_writing {
-            let (new_entry, stop_writing) = self.input_boxes.keypress(
-                &self.get_layout(terminal_size)[2..],
-                &mut self.cursor,
-                key,
-            )?;
-            if let Some(new_entry) = new_entry {
-                if self.editing_entry.is_some() {
-                    self.merge_entry(new_entry)?;
-                    self.editing_entry = None;
-                } else {
-                    self.add_entry(new_entry, self.next_id)?;
-                    self.next_id += 1;
-                }
+    /// (position, number) of the entry at the top of the list given the current scroll
+    /// Counts rows with lines wrapped to `width`, like the list on screen
+    /// None if no entries are displayed
+    fn scroll_position(
+        &self,
+        styled_blocks: &[(Option<u64>, Vec<Text>)],
+        width: u16,
+    ) -> Option<(usize, usize)> {
+        let total = styled_blocks.iter().filter(|(id, _)| id.is_some()).count();
+        let mut line = 0;
+        let mut index = 0;
+        for (id, texts) in styled_blocks {
+            if line > self.scroll as usize {
+                break;
             }
-            if stop_writing {
-                self.is_writing = false;
-                self.cursor.hide()?;
+            if id.is_some() {
+                index += 1;
             }
+            line += utility::formatting::count_wrapped_lines(texts, width);
+        }
+        if total == 0 {
+            None
         } else {
-            match key {
-                KeyEvent::Char(c) => match c {
-                    'n' => {
-                        self.is_writing = true;
-                        self.input_boxes
-                            .start_writing(&self.get_layout(terminal_size)[2..], &mut self.cursor)?;
-                    }
-                    '\t' => self.toggle_fold(),
-                    't' | 'e' | 'd' => {
-                        self.picking_char = Some(c);
-                        self.picking_entry = true;
-                        self.selected_entry = 0;
-                    }
-                    '1' | '2' | '3' | '4' | '5' | '6' | '7' | '8' | '9' | '0' => {
-                        if self.picking_entry {
-                            if self.selected_entry > 0 {
-                                self.selected_entry =
-                                    format!("{}{}", self.selected_entry, c).parse()?;
-                            } else {
-                                self.selected_entry = c.to_string().parse()?;
-                            }
-                        }
-                    }
-                    '\n' => {
-                        if let Some(c) = self.picking_char {
-                            match c {
-                                't' => self.toggle_task_entry()?,
-                                'e' => self.start_editing()?,
-                                'd' => self.delete_entry(self.selected_entry)?,
-                                _ => (),
-                            }
-                        }
-                        self.picking_entry = false;
-                        self.selected_entry = 0;
-                        self.picking_char = None;
-                    }
-                    _ => (),
-                },
-                KeyEvent::Down => self.scroll += 1,
-                KeyEvent::Up => {
-                    if self.scroll > 0 {
-                        self.scroll -= 1;
-                    }
-                }
-                _ => (),
-            }
+            Some((index.max(1), total))
         }
-        Ok(())
     }
 
-    /// fold = true => short display (title, date, tags)
-    /// fold = false => displays everything
-    pub fn toggle_fold(&mut self) {
-        self.fold = !self.fold;
+    /// What to show instead of an empty list
+    fn empty_message(&self) -> &'static str {
+        if self.entries.is_empty() {
+            "No entries yet, n : write one"
+        } else if self.filter.is_empty() && self.hide_done {
+            "Everything's done, h : show done Tasks"
+        } else if self.agenda && !self.visible_ids.is_empty() {
+            "Nothing with a due date, v : back to the list"
+        } else {
+            "Nothing matches the filter, Esc : clear it"
+        }
     }
 
-    /// Put an existing entry into text input boxes for editing
-    fn start_editing(&mut self) -> Result<(), Error> {
+    /// false for done Tasks while they're hidden
+    fn shows_done(&self, g_entry: &entry::GooseberryEntry) -> bool {
+        !(self.hide_done && g_entry.is_done())
+    }
+
+    /// true => the entry belongs in `visible_ids`
+    fn shows(&self, g_entry: &entry::GooseberryEntry) -> bool {
+        self.filter.matches(g_entry) && self.shows_done(g_entry)
+    }
+
+    /// Shows only the entries matching `filter` (done Tasks stay hidden if they're hidden)
+    /// If nothing matches and `CONFIG.clear_empty_filter` is set, the filter is dropped instead
+    pub fn apply_filter(&mut self, filter: EntryFilter) {
+        let visible_ids = self
+            .entries
+            .values()
+            .filter(|e| filter.matches(e) && self.shows_done(e))
+            .map(|e| e.id())
+            .collect::<Vec<_>>();
+        if visible_ids.is_empty() && !self.entries.is_empty() && !filter.is_empty() {
+            if CONFIG.clear_empty_filter {
+                self.clear_filter();
+                self.add_message("Nothing matched, filter cleared".into());
+                return;
+            }
+            self.add_message("Nothing matches the filter".into());
+        }
+        self.visible_ids = visible_ids;
+        self.filter = filter;
+        self.scroll = 0;
+    }
+
+    /// true => keys go to the line being typed in the help box title
+    pub fn is_typing(&self) -> bool {
+        self.typing.is_some()
+    }
+
+    /// true => keys answer the delete prompt
+    pub fn is_confirming(&self) -> bool {
+        self.deleting.is_some()
+    }
+
+    /// Does what the typed text was for, `c` is the key that started typing
+    fn finish_typing(&mut self, c: char, text: &str) {
+        let text = text.trim();
+        let text = if text.is_empty() {
+            None
+        } else {
+            Some(text.to_owned())
+        };
+        match c {
+            '/' => self.apply_filter(EntryFilter {
+                tag: text,
+                ..self.filter.clone()
+            }),
+            '?' => self.search(text.as_deref().unwrap_or_default()),
+            _ => (),
+        }
+    }
+
+    /// Shows only entries with `query` in their text (title, description, notes etc., ignoring case)
+    /// Keeps any tag filter, an empty query shows everything the tag filter lets through
+    pub fn search(&mut self, query: &str) {
+        let text = if query.is_empty() {
+            None
+        } else {
+            Some(query.to_owned())
+        };
+        self.apply_filter(EntryFilter {
+            text,
+            ..self.filter.clone()
+        });
+    }
+
+    /// Shows all the entries again (except done Tasks if they're hidden)
+    pub fn clear_filter(&mut self) {
+        self.filter = EntryFilter::default();
+        self.visible_ids = self
+            .entries
+            .values()
+            .filter(|e| self.shows_done(e))
+            .map(|e| e.id())
+            .collect();
+        self.scroll = 0;
+    }
+
+    /// Hides or shows done Tasks, keeping the filter, does nothing in other tabs
+    pub fn toggle_hide_done(&mut self) {
+        if self.entry_type != entry::GooseberryEntryType::Task {
+            return;
+        }
+        self.hide_done = !self.hide_done;
+        self.apply_filter(self.filter.clone());
+        let message = if self.hide_done {
+            "Hiding done Tasks"
+        } else {
+            "Showing done Tasks"
+        };
+        self.add_message(message.into());
+    }
+
+    /// Switches between the list and the agenda (grouped by day), does nothing in other tabs
+    pub fn toggle_agenda(&mut self) {
+        let message = match self.entry_type {
+            entry::GooseberryEntryType::Event => "Agenda view",
+            entry::GooseberryEntryType::Task => "Agenda view, Tasks without a due date aren't shown",
+            _ => return,
+        };
+        self.agenda = !self.agenda;
+        self.scroll = 0;
+        self.add_message(if self.agenda { message } else { "List view" }.into());
+    }
+
+    /// e.g. "Task (12)", or "Task (3/12)" when some are hidden by the filter or `h`
+    fn title_with_count(&self) -> String {
+        let (visible, total) = (self.visible_ids.len(), self.entries.len());
+        if visible < total {
+            format!("{} ({}/{})", self.title, visible, total)
+        } else {
+            format!("{} ({})", self.title, total)
+        }
+    }
+
+    /// Says how many entries are hidden, None if everything is shown
+    fn hidden_indicator(&self) -> Option<String> {
+        let (visible, total) = (self.visible_ids.len(), self.entries.len());
+        if visible < total {
+            Some(format!(
+                "Showing {} of {} entries ({} hidden)",
+                visible,
+                total,
+                total - visible
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Called when user inputs `t <id>[Enter]` in the Task tab
+    /// toggles the state of a Task entry (done/not done)
+    /// If the write fails the toggle is undone so memory and disk agree
+    fn toggle_task_entry(&mut self) -> Result<(), Error> {
+        if self.entry_type == entry::GooseberryEntryType::Task {
+            let done = self.toggle_task(self.selected_entry)?;
+            match self.save_entry(self.selected_entry) {
+                Ok(()) => {
+                    let id = self.selected_entry;
+                    self.log_activity(ActivityKind::Toggled, id);
+                    let state = if done { "done" } else { "not done" };
+                    self.message = Some(format!("Task {} marked {}", id, state));
+                    if !self.shows_done(&self.entries[&id]) {
+                        self.visible_ids.remove_item(&id);
+                    }
+                }
+                Err(error) => {
+                    self.toggle_task(self.selected_entry)?;
+                    self.unsaved_ids.remove(&self.selected_entry);
+                    self.message = Some(format!(
+                        "Couldn't save Task {}, toggle undone: {}",
+                        self.selected_entry, error
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Flips the done state of a Task entry in memory, returns the new state
+    fn toggle_task(&mut self, id: u64) -> Result<bool, Error> {
+        let t_entry = self.entries.get_mut(&id).ok_or(Sorry::MissingEntryID {
+            entry_type: self.entry_type,
+            entry_id: id,
+        })?;
+        match t_entry {
+            entry::GooseberryEntry::Task(ref mut t) => {
+                t.toggle();
+                Ok(t.done)
+            }
+            _ => Err(Sorry::WrongEntryType {
+                expected: entry::GooseberryEntryType::Task,
+                got: t_entry.entry_type(),
+            }
+                .into()),
+        }
+    }
+
+    /// Handles keyboard input
+    /// in writing mode, keys go to the input boxes, but Ctrl-s doesn't save while a required box is empty
+    /// in scrolling mode:
+    ///     ^ v: scrolls by `CONFIG.scroll_step` lines
+    ///     PageUp PageDown: scrolls by `CONFIG.coarse_scroll_step` lines
+    ///     n: starts/resumes writing mode
+    ///     N: throws away the paused draft (or edit) and starts writing a new entry
+    ///     g: in the Journal tab, scrolls to today's entries or starts writing one if there aren't any
+    ///     `\t`: cycles through full, fold and compact display
+    ///     Z: collapses all entries (or expands all if they're already collapsed)
+    ///     o: cycles what the entries are sorted by, O: flips ascending/descending
+    ///     h: in the Task tab, hides/shows done Tasks
+    ///     w: tries saving entries whose last save failed again
+    ///     /: starts typing a tag, `\n` then shows only entries with that tag (or a tag under it)
+    ///     ?: starts typing a search, `\n` then shows only entries containing it (highlighted)
+    ///     Esc: clears the filter (or cancels typing)
+    ///     e/E/A/t/d/a/z/y/l/k/j: starts ID entry mode
+    ///     0-9: if in ID entry mode, adds the digit to `self.selected_entry`
+    ///     Backspace: if in ID entry mode, removes the last digit
+    ///     `\n`: stops ID entry mode and executes e/E/A/t/d/a/z/y/l/k/j (cancels if no id was typed)
+    ///     y/n/Esc: after d, deletes the entry or cancels
+    /// Other keys ring the bell or show a message if `CONFIG.unknown_key` says so
+    pub fn keypress(&mut self, terminal_size: Rect, key: KeyEvent) -> Result<(), Error> {
+        if self.is_writing {
+            if !utility::interactive::InputBoxes::handles(&key) {
+                self.message = utility::interactive::unknown_key(&key)?;
+            }
+            if let KeyEvent::Ctrl('s') | KeyEvent::Ctrl('w') = key {
+                let problems = self.input_boxes.problems();
+                if !problems.is_empty() {
+                    self.message = Some(format!("Can't save: {}", problems.join(", ")));
+                    return Ok(());
+                }
+            }
+            let (new_entry, stop_writing) = self.input_boxes.keypress(
+                &self.get_layout(terminal_size)[2..],
+                &mut self.cursor,
+                key,
+            )?;
+            // a failed save keeps the entry in memory (marked unsaved) and shows an error banner
+            if let Some(new_entry) = new_entry {
+                let (id, saved, kept) = match self.editing_entry.as_ref().map(|e| e.id()) {
+                    Some(id) if self.appending => (id, self.append_entry(new_entry), true),
+                    Some(id) => (id, self.merge_entry(new_entry), true),
+                    None => {
+                        let id = self.next_id;
+                        let taken = self.entries.contains_key(&id);
+                        let saved = self.add_entry(new_entry.clone(), id);
+                        if self.entries.contains_key(&id) {
+                            self.next_id += 1;
+                            self.store_next_id();
+                        }
+                        let added = !taken && self.entries.contains_key(&id);
+                        if saved.is_ok() {
+                            self.remove_draft()?;
+                        } else if !added {
+                            // nothing was added (e.g. the ID was taken), so the text goes back in the boxes
+                            for (i, input_box) in new_entry.iter().enumerate() {
+                                self.input_boxes.replace_content(i, &input_box.get_content());
+                            }
+                            self.save_draft()?;
+                        }
+                        (id, saved, added)
+                    }
+                };
+                if stop_writing {
+                    self.editing_entry = None;
+                    self.appending = false;
+                } else if kept {
+                    // Ctrl-w: the next save changes the entry that was just saved instead of adding another,
+                    // and appended text isn't appended twice
+                    self.editing_entry = self.entries.get(&id).cloned();
+                    if self.appending {
+                        self.input_boxes.clear();
+                    }
+                    if saved.is_ok() {
+                        self.message = Some(format!("Saved {} {}, still writing", self.entry_type, id));
+                    }
+                }
+                if let Err(error) = saved {
+                    warn!("Not saved from writing mode: {}", error);
+                    self.save_error = Some(format!("Not saved: {}", error));
+                }
+            } else if stop_writing {
+                self.save_draft()?;
+            }
+            if stop_writing {
+                self.is_writing = false;
+                self.cursor.hide()?;
+            }
+        } else if let Some(id) = self.deleting.take() {
+            match key {
+                KeyEvent::Char('y') => {
+                    if let Err(error) = self.delete_entry(id) {
+                        self.message = Some(error.to_string());
+                    }
+                }
+                KeyEvent::Char('n') | KeyEvent::Esc => {
+                    self.message = Some(format!("{} {} not deleted", self.entry_type, id))
+                }
+                _ => self.deleting = Some(id),
+            }
+        } else if let Some((c, mut text)) = self.typing.take() {
+            match key {
+                KeyEvent::Char('\n') => self.finish_typing(c, &text),
+                KeyEvent::Char(t) => {
+                    text.push(t);
+                    self.typing = Some((c, text));
+                }
+                KeyEvent::Backspace => {
+                    text.pop();
+                    self.typing = Some((c, text));
+                }
+                KeyEvent::Esc => (),
+                _ => self.typing = Some((c, text)),
+            }
+        } else {
+            self.message = None;
+            match key {
+                KeyEvent::Char(c) => match c {
+                    '/' | '?' => self.typing = Some((c, String::new())),
+                    'n' => self.start_writing(terminal_size)?,
+                    'N' => self.start_new_entry(terminal_size)?,
+                    'g' => self.goto_today(terminal_size)?,
+                    '\t' => self.cycle_display_mode(),
+                    'Z' => self.toggle_collapse_all(),
+                    'h' => self.toggle_hide_done(),
+                    'v' => self.toggle_agenda(),
+                    'o' => self.cycle_sort_key(),
+                    'O' => self.flip_sort_direction(),
+                    'w' => self.retry_saves(),
+                    't' | 'e' | 'E' | 'A' | 'd' | 'a' | 'z' | 'y' | 'l' | 'k' | 'j' => {
+                        self.picking_char = Some(c);
+                        self.picking_entry = true;
+                        self.selected_entry = 0;
+                    }
+                    '1' | '2' | '3' | '4' | '5' | '6' | '7' | '8' | '9' | '0' => {
+                        if self.picking_entry {
+                            if self.selected_entry > 0 {
+                                self.selected_entry =
+                                    format!("{}{}", self.selected_entry, c).parse()?;
+                            } else {
+                                self.selected_entry = c.to_string().parse()?;
+                            }
+                        }
+                    }
+                    '\n' => {
+                        // ids start at 1, so 0 means nothing was typed
+                        if self.picking_entry && self.selected_entry == 0 {
+                            self.message = Some("No id entered, cancelled".into());
+                        } else if let Some(c) = self.picking_char {
+                            match c {
+                                't' => self.toggle_task_entry()?,
+                                'e' => self.start_editing()?,
+                                'E' => self.request_editor(self.selected_entry)?,
+                                'A' => self.start_appending()?,
+                                'd' => self.confirm_delete(self.selected_entry)?,
+                                // archiving twice (or a missing id) isn't worth quitting over
+                                'a' => {
+                                    if let Err(error) = self.archive_entry(self.selected_entry) {
+                                        self.message = Some(error.to_string());
+                                    }
+                                }
+                                'z' => self.toggle_collapse(self.selected_entry)?,
+                                'y' => self.copy_entry(self.selected_entry)?,
+                                'l' => self.reload_picked(self.selected_entry),
+                                'k' => self.move_entry(self.selected_entry, true)?,
+                                'j' => self.move_entry(self.selected_entry, false)?,
+                                _ => (),
+                            }
+                        }
+                        self.picking_entry = false;
+                        self.selected_entry = 0;
+                        self.picking_char = None;
+                    }
+                    _ => self.message = utility::interactive::unknown_key(&key)?,
+                },
+                KeyEvent::Backspace => {
+                    if self.picking_entry {
+                        self.selected_entry /= 10;
+                    }
+                }
+                KeyEvent::Esc if !self.filter.is_empty() => {
+                    self.clear_filter();
+                    self.message = Some("Filter cleared".into());
+                }
+                KeyEvent::Down => self.scroll_down(CONFIG.scroll_step, terminal_size)?,
+                KeyEvent::Up => self.scroll_up(CONFIG.scroll_step),
+                KeyEvent::PageDown => self.scroll_down(CONFIG.coarse_scroll_step, terminal_size)?,
+                KeyEvent::PageUp => self.scroll_up(CONFIG.coarse_scroll_step),
+                _ => self.message = utility::interactive::unknown_key(&key)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Scrolls to the first of today's Journal entries, or starts a new one if there are none
+    fn goto_today(&mut self, terminal_size: Rect) -> Result<(), Error> {
+        if self.entry_type != entry::GooseberryEntryType::Journal {
+            self.message = Some("g only works in the Journal tab".into());
+            return Ok(());
+        }
+        let today = Utc::now().date_naive();
+        let first_today = self
+            .visible_ids
+            .iter()
+            .filter(|id| self.entries[id].datetime().date_naive() == today)
+            .min_by(|a, b| query::compare_created(&self.entries[a], &self.entries[b]))
+            .cloned();
+        match first_today {
+            Some(id) => self.scroll_to_entry(id, terminal_size)?,
+            None => self.start_writing(terminal_size)?,
+        }
+        Ok(())
+    }
+
+    /// Sets the scroll so the entry (and its Journal date header if it's the first of the day)
+    /// is at the top of the list, does nothing if the entry isn't displayed
+    fn scroll_to_entry(&mut self, id: u64, terminal_size: Rect) -> Result<(), Error> {
+        let list_box = self.get_layout(terminal_size)[1];
+        let styled_blocks = entry::GooseberryEntry::entries_to_styled_blocks_same_type(
+            &self.entries,
+            self.list_view(),
+            self.list_width(terminal_size),
+        )?;
+        if let Some(index) = styled_blocks.iter().position(|(i, _)| *i == Some(id)) {
+            let start = match index {
+                0 => 0,
+                _ if styled_blocks[index - 1].0.is_none() => index - 1,
+                _ => index,
+            };
+            let lines: usize = styled_blocks[..start]
+                .iter()
+                .map(|(_, texts)| {
+                    utility::formatting::count_wrapped_lines(texts, list_box.width.saturating_sub(2))
+                })
+                .sum();
+            self.scroll = lines as u16;
+        }
+        Ok(())
+    }
+
+    /// Stops when the last line of the list is at the bottom of the list box
+    fn scroll_down(&mut self, step: u16, terminal_size: Rect) -> Result<(), Error> {
+        let max_scroll = self.max_scroll(terminal_size)?;
+        self.scroll = self.scroll.saturating_add(step).min(max_scroll);
+        Ok(())
+    }
+
+    /// How far the list can be scrolled before the rest of the list box would be empty
+    fn max_scroll(&self, terminal_size: Rect) -> Result<u16, Error> {
+        let list_box = self.get_layout(terminal_size)[1];
+        // same width as in `render`
+        let styled_blocks = entry::GooseberryEntry::entries_to_styled_blocks_same_type(
+            &self.entries,
+            self.list_view(),
+            self.list_width(terminal_size),
+        )?;
+        let lines: usize = styled_blocks
+            .iter()
+            .map(|(_, texts)| {
+                utility::formatting::count_wrapped_lines(texts, list_box.width.saturating_sub(2))
+            })
+            .sum();
+        let height = list_box.height.saturating_sub(2) as usize;
+        Ok(lines.saturating_sub(height).min(u16::MAX as usize) as u16)
+    }
+
+    /// Stops at the top even if the step is bigger than the current scroll
+    fn scroll_up(&mut self, step: u16) {
+        self.scroll = self.scroll.saturating_sub(step);
+    }
+
+    /// Hides/shows the description of one entry in Full mode
+    fn toggle_collapse(&mut self, id: u64) -> Result<(), Error> {
+        if !self.entries.contains_key(&id) {
+            return Err(Sorry::MissingEntryID {
+                entry_type: self.entry_type,
+                entry_id: id,
+            }
+            .into());
+        }
+        if !self.collapsed_ids.remove(&id) {
+            self.collapsed_ids.insert(id);
+        }
+        Ok(())
+    }
+
+    /// Collapses every entry, unless they're all collapsed already in which case expands them all
+    /// Independent of the display mode, only has a visible effect in Full mode
+    pub fn toggle_collapse_all(&mut self) {
+        if self.entries.keys().all(|id| self.collapsed_ids.contains(id)) {
+            self.collapsed_ids.clear();
+        } else {
+            self.collapsed_ids = self.entries.keys().cloned().collect();
+        }
+    }
+
+    /// Sets the message, or tacks it onto the current one so neither gets lost
+    fn add_message(&mut self, message: String) {
+        self.message = Some(match self.message.take() {
+            Some(current) => format!("{}; {}", current, message),
+            None => message,
+        });
+    }
+
+    /// true if the input boxes hold a draft that hasn't been saved (writing or paused)
+    pub fn has_unsaved_draft(&self) -> bool {
+        self.is_writing || self.input_boxes.has_content()
+    }
+
+    /// Full => displays everything
+    /// Fold => short display (title, date, tags)
+    /// Compact => one line per entry (title, short date)
+    pub fn cycle_display_mode(&mut self) {
+        self.display_mode = self.display_mode.next();
+    }
+
+    /// Date => as listed (entries moved with k/j keep their place), then Id, Title, and for Tasks Done and Priority
+    pub fn cycle_sort_key(&mut self) {
+        self.list_order.key = self.list_order.key.next(self.entry_type);
+        self.message = Some(format!("Sorted by {}", self.list_order));
+    }
+
+    pub fn flip_sort_direction(&mut self) {
+        self.list_order.descending = !self.list_order.descending;
+        self.message = Some(format!("Sorted by {}", self.list_order));
+    }
+
+    /// Starts writing mode, resuming whatever is in the input boxes
+    pub fn start_writing(&mut self, terminal_size: Rect) -> Result<(), Error> {
+        self.is_writing = true;
+        self.input_boxes
+            .start_writing(&self.get_layout(terminal_size)[2..], &mut self.cursor)
+    }
+
+    /// Empties the input boxes and starts writing a new entry
+    /// An entry that was being edited is left as it was
+    fn start_new_entry(&mut self, terminal_size: Rect) -> Result<(), Error> {
+        if self.editing_entry.take().is_some() || self.input_boxes.has_content() {
+            self.message = Some("Discarded the unsaved draft".into());
+        }
+        self.appending = false;
+        self.input_boxes = self.entry_type.get_input_boxes();
+        self.remove_draft()?;
+        self.start_writing(terminal_size)
+    }
+
+    /// Keeps a paused new entry in the folder so it's still there next time
+    /// Edits of existing entries aren't kept (they'd come back as a new entry)
+    fn save_draft(&self) -> Result<(), Error> {
+        if self.dry_run || self.editing_entry.is_some() {
+            return Ok(());
+        }
+        if self.input_boxes.has_content() {
+            entry::write_atomically(
+                &self.entry_type.draft_path(&self.folder),
+                &self.input_boxes.to_draft(),
+            )?;
+            Ok(())
+        } else {
+            self.remove_draft()
+        }
+    }
+
+    /// Keeps `next_id` in the folder for the next run, a failed write is only logged
+    /// (the next run still starts after the IDs it finds)
+    fn store_next_id(&self) {
+        if self.dry_run {
+            return;
+        }
+        let stored = entry::write_atomically(
+            &self.entry_type.next_id_path(&self.folder),
+            &self.next_id.to_string(),
+        );
+        if let Err(error) = stored {
+            warn!("Couldn't store the next {} ID: {}", self.entry_type, error);
+        }
+    }
+
+    /// Called when the draft is saved as an entry or thrown away
+    fn remove_draft(&self) -> Result<(), Error> {
+        let draft_path = self.entry_type.draft_path(&self.folder);
+        if !self.dry_run && draft_path.exists() {
+            PathFile::new(draft_path)?.remove()?;
+        }
+        Ok(())
+    }
+
+    /// Put an existing entry into text input boxes for editing
+    fn start_editing(&mut self) -> Result<(), Error> {
         let entry = self
             .entries
             .get(&self.selected_entry)
@@ -370,23 +1718,85 @@ impl GooseberryTab {
         self.input_boxes = entry.to_input_boxes();
         self.is_writing = true;
         self.editing_entry = Some(entry);
+        self.appending = false;
         Ok(())
     }
 
-    /// Write entry to file
-    fn save_entry(&self, id: u64) -> Result<(), Error> {
-        self.entries
-            .get(&id)
+    /// Opens an empty box whose content is added to the end of an existing entry
+    fn start_appending(&mut self) -> Result<(), Error> {
+        let entry = self
+            .entries
+            .get(&self.selected_entry)
             .ok_or(Sorry::MissingEntryID {
                 entry_type: self.entry_type,
-                entry_id: id,
+                entry_id: self.selected_entry,
             })?
-            .to_file(PathFile::create(
-                self.entry_type.get_file(&self.folder, id)?,
-            )?)?;
+            .clone();
+        self.input_boxes = self.entry_type.get_append_box();
+        self.is_writing = true;
+        self.editing_entry = Some(entry);
+        self.appending = true;
         Ok(())
     }
 
+    /// Write entry to file (skipped in a dry run)
+    /// Invalid entries (see `validate`) aren't written
+    /// Refuses to overwrite a file that holds a different entry
+    /// Entries that fail to save are marked as unsaved until the next successful save
+    fn save_entry(&mut self, id: u64) -> Result<(), Error> {
+        let g_entry = self.entries.get(&id).ok_or(Sorry::MissingEntryID {
+            entry_type: self.entry_type,
+            entry_id: id,
+        })?;
+        let valid = g_entry.validate().map_err(|problems| Sorry::InvalidEntry {
+            entry_type: self.entry_type,
+            entry_id: id,
+            problems,
+        });
+        if let Err(error) = valid {
+            warn!("Not saving {}", error);
+            self.unsaved_ids.insert(id);
+            return Err(error.into());
+        }
+        if self.dry_run {
+            return Ok(());
+        }
+        let saved = self
+            .entry_type
+            .check_file_id(&self.folder, id)
+            .and_then(|_| self.entry_type.get_file(&self.folder, id))
+            .and_then(|file| g_entry.to_file(file));
+        match saved {
+            Ok(()) => {
+                debug!("Saved {} {}", self.entry_type, id);
+                self.unsaved_ids.remove(&id);
+                if self.unsaved_ids.is_empty() {
+                    self.save_error = None;
+                }
+                Ok(())
+            }
+            Err(error) => {
+                error!("Couldn't save {} {}: {}", self.entry_type, id, error);
+                self.unsaved_ids.insert(id);
+                Err(error.into())
+            }
+        }
+    }
+
+    /// Tries writing the entries whose last save failed again
+    fn retry_saves(&mut self) {
+        let mut ids = self.unsaved_ids.iter().copied().collect::<Vec<_>>();
+        ids.sort();
+        for id in ids {
+            if let Err(error) = self.save_entry(id) {
+                self.save_error = Some(format!("Not saved: {}", error));
+                return;
+            }
+        }
+        self.save_error = None;
+        self.message = Some("Everything's saved".into());
+    }
+
     /// Get an entry from input boxes after Ctrl-s in writing mode, merge it with the previous, save it to file
     fn merge_entry(&mut self, boxes: Vec<utility::interactive::InputBox>) -> Result<(), Error> {
         let editing_entry = self.editing_entry.as_ref().ok_or(Sorry::OutOfCheeseError {
@@ -396,24 +1806,319 @@ impl GooseberryTab {
         let mut new_entry = entry::GooseberryEntry::from_input_boxes(id, self.entry_type, boxes)?;
         new_entry.merge_with_entry(editing_entry);
         self.entries.insert(id, new_entry);
+        self.log_activity(ActivityKind::Edited, id);
         self.save_entry(id)?;
         Ok(())
     }
 
+    /// Adds the appended text after Ctrl-s to the entry being appended to, save it to file
+    /// Uses the entry as it is now, in case it changed since appending started
+    fn append_entry(&mut self, boxes: Vec<utility::interactive::InputBox>) -> Result<(), Error> {
+        let id = self
+            .editing_entry
+            .as_ref()
+            .ok_or(Sorry::OutOfCheeseError {
+                message: "Appending to nothing?".into(),
+            })?
+            .id();
+        let text = boxes[0].get_content();
+        if text.trim().is_empty() {
+            return Ok(());
+        }
+        self.entries
+            .get_mut(&id)
+            .ok_or(Sorry::MissingEntryID {
+                entry_type: self.entry_type,
+                entry_id: id,
+            })?
+            .append_to_body(&text);
+        self.log_activity(ActivityKind::Appended, id);
+        self.save_entry(id)
+    }
+
     /// Get an entry from input boxes after Ctrl-s in writing mode, save it to file
+    /// Refuses an ID that's already taken in this tab
     fn add_entry(
         &mut self,
         boxes: Vec<utility::interactive::InputBox>,
         id: u64,
     ) -> Result<(), Error> {
+        if self.entries.contains_key(&id) {
+            return Err(Sorry::DuplicateEntryID {
+                entry_type: self.entry_type,
+                entry_id: id,
+            }
+            .into());
+        }
         let new_entry = entry::GooseberryEntry::from_input_boxes(id, self.entry_type, boxes)?;
         self.entries.insert(id, new_entry);
-        self.visible_ids.push(id);
+        if !self.visible_ids.contains(&id) {
+            self.visible_ids.push(id);
+        }
+        self.log_activity(ActivityKind::Added, id);
         self.save_entry(id)?;
         Ok(())
     }
 
-    /// Deletes an entry
+    /// Moves an entry one place up or down the list, past the next entry that's shown
+    /// If both entries already have an `order` they swap them, so only the two are saved
+    /// Otherwise the entries up to the further of the two (and any others with an order) are numbered
+    /// by their new place, and only the ones whose order changed are saved
+    /// Save errors are shown in the message (the entries stay marked unsaved), not returned
+    fn move_entry(&mut self, id: u64, up: bool) -> Result<(), Error> {
+        let missing = || Sorry::MissingEntryID {
+            entry_type: self.entry_type,
+            entry_id: id,
+        };
+        if !self.entries.contains_key(&id) {
+            self.message = Some(missing().to_string());
+            return Ok(());
+        }
+        if !self.list_order.is_listed() || self.agenda {
+            self.message = Some(
+                "Entries can only be moved in the list view sorted by Date, ascending (o/O, v)".into(),
+            );
+            return Ok(());
+        }
+        let mut ids = self.entries.keys().copied().collect::<Vec<_>>();
+        ids.sort_by(|a, b| query::compare_listed(&self.entries[a], &self.entries[b]));
+        let position = ids.iter().position(|i| *i == id).ok_or_else(missing)?;
+        let neighbour = if up {
+            ids[..position]
+                .iter()
+                .rposition(|i| self.visible_ids.contains(i))
+        } else {
+            ids[position + 1..]
+                .iter()
+                .position(|i| self.visible_ids.contains(i))
+                .map(|i| i + position + 1)
+        };
+        let neighbour = match neighbour {
+            Some(neighbour) => neighbour,
+            None => {
+                let end = if up { "top" } else { "bottom" };
+                self.message = Some(format!("{} {} is already at the {}", self.entry_type, id, end));
+                return Ok(());
+            }
+        };
+        let neighbour_id = ids[neighbour];
+        let new_orders = match (self.entries[&id].order(), self.entries[&neighbour_id].order()) {
+            (Some(order), Some(neighbour_order)) if order != neighbour_order => {
+                vec![(id, Some(neighbour_order)), (neighbour_id, Some(order))]
+            }
+            _ => {
+                ids.remove(position);
+                ids.insert(neighbour, id);
+                // entries with an order are listed before the ones without,
+                // so everything up to the last one with an order needs one
+                let last = ids
+                    .iter()
+                    .rposition(|i| self.entries[i].order().is_some())
+                    .map_or(position.max(neighbour), |last| last.max(position.max(neighbour)));
+                ids[..=last]
+                    .iter()
+                    .enumerate()
+                    .map(|(order, other_id)| (*other_id, Some(order as i64)))
+                    .collect()
+            }
+        };
+        let mut errors = Vec::new();
+        for (other_id, order) in new_orders {
+            if let Some(g_entry) = self.entries.get_mut(&other_id) {
+                if g_entry.order() != order {
+                    g_entry.set_order(order);
+                    if let Err(error) = self.save_entry(other_id) {
+                        errors.push(error.to_string());
+                    }
+                }
+            }
+        }
+        self.log_activity(ActivityKind::Moved, id);
+        if !errors.is_empty() {
+            warn!("Moving {} {}: {}", self.entry_type, id, errors.join("; "));
+            self.save_error = Some(format!("Not saved: {}", errors.join("; ")));
+        }
+        Ok(())
+    }
+
+    /// Marks an entry to be opened in `$EDITOR` once the key is handled, unsaved changes are saved first
+    /// Not in a dry run, the editor would change the file
+    fn request_editor(&mut self, id: u64) -> Result<(), Error> {
+        if self.dry_run {
+            self.message = Some("Can't open files in an editor in a dry run".into());
+            return Ok(());
+        }
+        if !self.entries.contains_key(&id) {
+            return Err(Sorry::MissingEntryID {
+                entry_type: self.entry_type,
+                entry_id: id,
+            }
+            .into());
+        }
+        if self.unsaved_ids.contains(&id) {
+            self.save_entry(id)?;
+        }
+        self.editor_entry = Some(id);
+        Ok(())
+    }
+
+    /// Re-reads the entry that was open in the editor
+    /// If the editor failed (or the file doesn't read back) the entry stays as it was
+    pub fn finish_editor(&mut self, edited: Result<(), Error>) {
+        let id = match self.editor_entry.take() {
+            Some(id) => id,
+            None => return,
+        };
+        self.message = Some(match edited.and_then(|_| self.reload_entry(id)) {
+            Ok(()) => format!("Reloaded {} {} after editing", self.entry_type, id),
+            Err(error) => {
+                warn!("Editing {} {} failed: {}", self.entry_type, id, error);
+                format!("Kept {} {} as it was: {}", self.entry_type, id, error)
+            }
+        });
+    }
+
+    /// Copies the entry's ID or file path (see `CONFIG.copy_target`) to the clipboard
+    /// A missing entry or a failed copy is only reported in the message
+    fn copy_entry(&mut self, id: u64) -> Result<(), Error> {
+        if !self.entries.contains_key(&id) {
+            self.message = Some(
+                Sorry::MissingEntryID {
+                    entry_type: self.entry_type,
+                    entry_id: id,
+                }
+                .to_string(),
+            );
+            return Ok(());
+        }
+        let text = match CONFIG.copy_target {
+            utility::interactive::CopyTarget::Id => id.to_string(),
+            utility::interactive::CopyTarget::Path => self
+                .entry_type
+                .file_path(&self.folder, id)
+                .to_string_lossy()
+                .into_owned(),
+        };
+        // a terminal that won't take it is worth a message, not quitting over
+        self.message = Some(match utility::interactive::copy_to_clipboard(&text) {
+            Ok(()) => format!("Copied {}", text),
+            Err(error) => format!("Couldn't copy {}: {}", text, error),
+        });
+        Ok(())
+    }
+
+    /// Re-reads a single entry from its file, e.g. after it was edited outside gooseberry
+    /// If the file is gone the entry is dropped too
+    pub fn reload_entry(&mut self, id: u64) -> Result<(), Error> {
+        let path = self.entry_type.file_path(&self.folder, id);
+        if !path.exists() {
+            if self.entries.remove(&id).is_some() {
+                self.log_activity(ActivityKind::Deleted, id);
+            }
+            self.visible_ids.remove_item(&id);
+            self.unsaved_ids.remove(&id);
+            self.collapsed_ids.remove(&id);
+            return Ok(());
+        }
+        let g_entry = entry::GooseberryEntry::from_file(&PathFile::new(&path)?)?;
+        if g_entry.entry_type() != self.entry_type {
+            return Err(Sorry::WrongEntryType {
+                expected: self.entry_type,
+                got: g_entry.entry_type(),
+            }
+            .into());
+        }
+        if g_entry.id() != id {
+            return Err(Sorry::PathCollision {
+                entry_type: self.entry_type,
+                entry_id: id,
+                other_id: g_entry.id(),
+                path: path.display().to_string(),
+            }
+            .into());
+        }
+        if !self.shows(&g_entry) {
+            self.visible_ids.remove_item(&id);
+        } else if !self.visible_ids.contains(&id) {
+            self.visible_ids.push(id);
+        }
+        self.entries.insert(id, g_entry);
+        self.unsaved_ids.remove(&id);
+        self.next_id = self.next_id.max(id + 1);
+        self.log_activity(ActivityKind::Reloaded, id);
+        Ok(())
+    }
+
+    /// `l <id>`: reloads an entry and says how it went, an unknown ID or an unreadable file
+    /// is only reported (the entry in memory is kept)
+    fn reload_picked(&mut self, id: u64) {
+        if !self.entries.contains_key(&id) && !self.entry_type.file_path(&self.folder, id).exists() {
+            let missing = Sorry::MissingEntryID {
+                entry_type: self.entry_type,
+                entry_id: id,
+            };
+            self.message = Some(missing.to_string());
+            return;
+        }
+        self.message = Some(match self.reload_entry(id) {
+            Ok(()) => format!("Reloaded {} {}", self.entry_type, id),
+            Err(error) => {
+                warn!("Couldn't reload {} {}: {}", self.entry_type, id, error);
+                format!("Kept {} {} as it was: {}", self.entry_type, id, error)
+            }
+        });
+    }
+
+    /// Moves an entry's file into the archive folder, where it's kept but not loaded
+    /// Unsaved changes are written first (in a dry run the entry is only hidden)
+    fn archive_entry(&mut self, id: u64) -> Result<(), Error> {
+        if !self.entries.contains_key(&id) {
+            return Err(Sorry::MissingEntryID {
+                entry_type: self.entry_type,
+                entry_id: id,
+            }
+            .into());
+        }
+        if !self.dry_run {
+            let archive_path = self.entry_type.archive_path(&self.folder, id);
+            if archive_path.exists() {
+                return Err(Sorry::AlreadyArchived {
+                    entry_type: self.entry_type,
+                    entry_id: id,
+                    path: archive_path.display().to_string(),
+                }
+                .into());
+            }
+            if self.unsaved_ids.contains(&id) {
+                self.save_entry(id)?;
+            }
+            if let Some(parent) = archive_path.parent() {
+                PathDir::create_all(parent)?;
+            }
+            fs::rename(self.entry_type.file_path(&self.folder, id), &archive_path)?;
+        }
+        self.entries.remove(&id);
+        self.visible_ids.remove_item(&id);
+        self.collapsed_ids.remove(&id);
+        self.log_activity(ActivityKind::Archived, id);
+        self.message = Some(format!("Archived {} {}", self.entry_type, id));
+        Ok(())
+    }
+
+    /// Asks before deleting an entry, `y` deletes it and `n` or Esc cancels
+    fn confirm_delete(&mut self, id: u64) -> Result<(), Error> {
+        if !self.entries.contains_key(&id) {
+            return Err(Sorry::MissingEntryID {
+                entry_type: self.entry_type,
+                entry_id: id,
+            }
+            .into());
+        }
+        self.deleting = Some(id);
+        Ok(())
+    }
+
+    /// Deletes an entry (the file stays in a dry run)
     fn delete_entry(&mut self, id: u64) -> Result<(), Error> {
         if !self.entries.contains_key(&id) {
             return Err(Sorry::MissingEntryID {
@@ -422,9 +2127,449 @@ impl GooseberryTab {
             }
                 .into());
         }
+        // the file goes first, so an entry that can't be deleted stays in the list
+        // (a file that's already gone is fine, the entry only lived in memory)
+        if !self.dry_run {
+            match fs::remove_file(self.entry_type.file_path(&self.folder, id)) {
+                Err(error) if error.kind() != io::ErrorKind::NotFound => {
+                    warn!("Couldn't delete {} {}: {}", self.entry_type, id, error);
+                    self.message = Some(format!(
+                        "Couldn't delete {} {}: {}",
+                        self.entry_type, id, error
+                    ));
+                    return Ok(());
+                }
+                _ => (),
+            }
+        }
         self.entries.remove(&id);
+        // the counter might only be known from the loaded files, keep it so the ID isn't given out again
+        self.store_next_id();
         self.visible_ids.remove_item(&id);
-        self.entry_type.get_file(&self.folder, id)?.remove()?;
+        self.unsaved_ids.remove(&id);
+        self.collapsed_ids.remove(&id);
+        self.log_activity(ActivityKind::Deleted, id);
         Ok(())
     }
+
+    /// Notes a change for the activity log
+    fn log_activity(&mut self, kind: ActivityKind, entry_id: u64) {
+        info!("{:?} {} {}", kind, self.entry_type, entry_id);
+        self.activity.push(Activity {
+            time: Utc::now(),
+            kind,
+            entry_type: self.entry_type,
+            entry_id,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::GooseberryError;
+    use crate::utility::test_folder;
+
+    /// Writes an entry file, `lines` are the type's own header lines
+    fn write_entry(
+        folder: &PathDir,
+        entry_type: entry::GooseberryEntryType,
+        id: u64,
+        day: u32,
+        tags: &str,
+        lines: &str,
+        body: &str,
+    ) {
+        fs::write(
+            entry_type.file_path(folder, id),
+            format!(
+                "---\nType: {}\nID: {}\nDateTime: 2020-01-{:02}T10:00:00Z\nTags: {}\n{}---\n{}",
+                entry_type, id, day, tags, lines, body
+            ),
+        )
+        .unwrap();
+    }
+
+    fn write_task(folder: &PathDir, id: u64, task: &str, done: bool) {
+        let lines = format!("Task: {}\nDone: {}\n", task, done);
+        write_entry(
+            folder,
+            entry::GooseberryEntryType::Task,
+            id,
+            1,
+            "work",
+            &lines,
+            "",
+        );
+    }
+
+    /// What main passes as the terminal size
+    const TERMINAL: Rect = Rect {
+        x: 0,
+        y: 0,
+        width: 100,
+        height: 40,
+    };
+
+    fn task_tab(folder: &PathDir) -> GooseberryTab {
+        GooseberryTab::from_folder(entry::GooseberryEntryType::Task, folder, false).unwrap()
+    }
+
+    #[test]
+    fn toggle_is_undone_when_the_save_fails() {
+        let folder = test_folder("toggle-fails");
+        write_task(&folder, 1, "Buy milk", false);
+        let mut tab = task_tab(&folder);
+        // a read-only folder doesn't stop root from writing, a file where the folder was stops everyone
+        let path = folder.as_path().to_owned();
+        folder.remove_all().unwrap();
+        fs::write(&path, "").unwrap();
+        tab.selected_entry = 1;
+        tab.toggle_task_entry().unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(!tab.entries[&1].is_done());
+        assert!(!tab.unsaved_ids.contains(&1));
+        assert!(tab.message.unwrap().starts_with("Couldn't save Task 1"));
+    }
+
+    #[test]
+    fn toggle_is_saved() {
+        let folder = test_folder("toggle-saves");
+        write_task(&folder, 1, "Buy milk", false);
+        let mut tab = task_tab(&folder);
+        tab.selected_entry = 1;
+        tab.toggle_task_entry().unwrap();
+        assert!(tab.entries[&1].is_done());
+        assert!(task_tab(&folder).entries[&1].is_done());
+        folder.remove_all().unwrap();
+    }
+
+    #[test]
+    fn unknown_header_survives_an_edit() {
+        let folder = test_folder("extra-header");
+        let path = entry::GooseberryEntryType::Task.file_path(&folder, 1);
+        fs::write(
+            &path,
+            "---\nType: Task\nID: 1\nDateTime: 2020-01-01T10:00:00Z\nTags: \nProject: gooseberry\nTask: Buy milk\nDone: false\n---\n",
+        )
+        .unwrap();
+        let mut tab = task_tab(&folder);
+        tab.selected_entry = 1;
+        tab.start_editing().unwrap();
+        for c in " now".chars() {
+            tab.keypress(TERMINAL, KeyEvent::Char(c)).unwrap();
+        }
+        tab.keypress(TERMINAL, KeyEvent::Ctrl('s')).unwrap();
+        let saved = fs::read_to_string(&path).unwrap();
+        folder.remove_all().unwrap();
+        assert!(saved.contains("Task: Buy milk now\n"));
+        assert!(saved.contains("Project: gooseberry\n"));
+    }
+
+    #[test]
+    fn enter_without_an_id_does_nothing() {
+        let folder = test_folder("empty-enter");
+        write_task(&folder, 1, "Buy milk", false);
+        let mut tab = task_tab(&folder);
+        tab.keypress(TERMINAL, KeyEvent::Char('t')).unwrap();
+        tab.keypress(TERMINAL, KeyEvent::Char('\n')).unwrap();
+        folder.remove_all().unwrap();
+        assert!(!tab.entries[&1].is_done());
+        assert!(!tab.picking_entry);
+        assert_eq!(tab.picking_char, None);
+        assert_eq!(tab.message.as_deref(), Some("No id entered, cancelled"));
+    }
+
+    #[test]
+    fn query_filters_across_tabs() {
+        use chrono::TimeZone;
+        use entry::GooseberryEntryType::{Event, Journal, Task};
+        let folder = test_folder("query");
+        write_entry(
+            &folder,
+            Task,
+            1,
+            1,
+            "work/gooseberry",
+            "Task: Buy milk\nDone: false\n",
+            "",
+        );
+        write_entry(
+            &folder,
+            Task,
+            2,
+            3,
+            "home",
+            "Task: Walk dog\nDone: true\n",
+            "",
+        );
+        write_entry(
+            &folder,
+            Event,
+            1,
+            2,
+            "work",
+            "Title: Seminar\nPeople: \n",
+            "",
+        );
+        write_entry(
+            &folder,
+            Journal,
+            1,
+            4,
+            "",
+            "",
+            "Slept through the SEMINAR\n",
+        );
+        let tabs = GooseberryTabs::from_folder(&folder, true).unwrap();
+        let ids = |filter: EntryFilter| {
+            tabs.query(&filter, SortBy::Created)
+                .iter()
+                .map(|e| (e.entry_type(), e.id()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ids(EntryFilter::default()),
+            vec![(Task, 1), (Event, 1), (Task, 2), (Journal, 1)]
+        );
+        assert_eq!(
+            ids(EntryFilter {
+                entry_type: Some(Task),
+                ..EntryFilter::default()
+            }),
+            vec![(Task, 1), (Task, 2)]
+        );
+        assert_eq!(
+            ids(EntryFilter {
+                tag: Some("work".into()),
+                ..EntryFilter::default()
+            }),
+            vec![(Task, 1), (Event, 1)]
+        );
+        assert_eq!(
+            ids(EntryFilter {
+                text: Some("seminar".into()),
+                ..EntryFilter::default()
+            }),
+            vec![(Event, 1), (Journal, 1)]
+        );
+        let day = |day| Utc.with_ymd_and_hms(2020, 1, day, 0, 0, 0).unwrap();
+        assert_eq!(
+            ids(EntryFilter {
+                from: Some(day(2)),
+                to: Some(day(4)),
+                ..EntryFilter::default()
+            }),
+            vec![(Event, 1), (Task, 2)]
+        );
+        assert_eq!(
+            ids(EntryFilter {
+                tag: Some("work".into()),
+                text: Some("seminar".into()),
+                ..EntryFilter::default()
+            }),
+            vec![(Event, 1)]
+        );
+        folder.remove_all().unwrap();
+    }
+
+    #[test]
+    fn files_without_an_id_in_the_name_are_skipped() {
+        let folder = test_folder("skipped-files");
+        write_task(&folder, 1, "Buy milk", false);
+        write_task(&folder, 2, "Walk dog", false);
+        fs::rename(
+            entry::GooseberryEntryType::Task.file_path(&folder, 2),
+            folder.as_path().join("Task_draft.md"),
+        )
+        .unwrap();
+        let tab = task_tab(&folder);
+        folder.remove_all().unwrap();
+        assert_eq!(tab.entries.keys().collect::<Vec<_>>(), vec![&1]);
+        assert_eq!(tab.visible_ids, vec![1]);
+        assert!(tab.message.unwrap().contains("Task_draft.md"));
+    }
+
+    #[test]
+    fn files_with_another_id_in_the_header_are_skipped() {
+        let folder = test_folder("mismatched-files");
+        write_task(&folder, 2, "Walk dog", false);
+        fs::rename(
+            entry::GooseberryEntryType::Task.file_path(&folder, 2),
+            entry::GooseberryEntryType::Task.file_path(&folder, 3),
+        )
+        .unwrap();
+        let tab = task_tab(&folder);
+        folder.remove_all().unwrap();
+        assert!(tab.entries.is_empty());
+        assert!(tab.message.unwrap().contains("Task_3.md"));
+        // so a new entry doesn't overwrite the file
+        assert_eq!(tab.next_id, 4);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn symlinked_folder_is_resolved() {
+        let folder = test_folder("symlink");
+        let real = PathDir::create_all(folder.as_path().join("real")).unwrap();
+        write_task(&real, 1, "Buy milk", false);
+        let link = folder.as_path().join("link");
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+        let mut tabs = GooseberryTabs::from_folder(&PathDir::new(&link).unwrap(), false).unwrap();
+        let tab = tabs
+            .tabs
+            .iter_mut()
+            .find(|t| t.entry_type == entry::GooseberryEntryType::Task)
+            .unwrap();
+        assert_eq!(tab.folder.as_path(), real.canonicalize().unwrap().as_path());
+        tab.selected_entry = 1;
+        tab.toggle_task_entry().unwrap();
+        drop(tabs);
+        let saved = fs::read_to_string(real.as_path().join("Task_1.md")).unwrap();
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        folder.remove_all().unwrap();
+        assert!(saved.contains("Done: true"));
+    }
+
+    #[test]
+    fn save_refuses_a_file_with_another_entry() {
+        let folder = test_folder("path-collision");
+        write_task(&folder, 1, "Buy milk", false);
+        let mut tab = task_tab(&folder);
+        // as if two entries were named the same by the file pattern
+        write_task(&folder, 2, "Walk dog", false);
+        let path = entry::GooseberryEntryType::Task.file_path(&folder, 1);
+        fs::rename(
+            entry::GooseberryEntryType::Task.file_path(&folder, 2),
+            &path,
+        )
+        .unwrap();
+        let error = tab.save_entry(1).unwrap_err();
+        let on_disk = fs::read_to_string(&path).unwrap();
+        folder.remove_all().unwrap();
+        match error.downcast_ref::<GooseberryError>() {
+            Some(GooseberryError::Sorry(Sorry::PathCollision {
+                entry_id: 1,
+                other_id: 2,
+                ..
+            })) => (),
+            _ => panic!("unexpected error: {}", error),
+        }
+        assert!(tab.unsaved_ids.contains(&1));
+        assert!(on_disk.contains("Walk dog"));
+    }
+
+    #[test]
+    fn failed_save_from_writing_mode_is_kept() {
+        let folder = test_folder("failed-save");
+        let mut tab = task_tab(&folder);
+        tab.keypress(TERMINAL, KeyEvent::Char('n')).unwrap();
+        for c in "Call bob".chars() {
+            tab.keypress(TERMINAL, KeyEvent::Char(c)).unwrap();
+        }
+        // a file where the folder was, so the save fails (even for root)
+        let path = folder.as_path().to_owned();
+        folder.clone().remove_all().unwrap();
+        fs::write(&path, "").unwrap();
+        tab.keypress(TERMINAL, KeyEvent::Ctrl('s')).unwrap();
+        assert!(!tab.is_writing);
+        assert_eq!(tab.entries[&1].title(), "Call bob");
+        assert!(tab.unsaved_ids.contains(&1));
+        assert!(tab.save_error.as_ref().unwrap().starts_with("Not saved"));
+        // w tries again once the folder is back
+        fs::remove_file(&path).unwrap();
+        fs::create_dir(&path).unwrap();
+        tab.keypress(TERMINAL, KeyEvent::Char('w')).unwrap();
+        let saved = entry::GooseberryEntryType::Task
+            .file_path(&folder, 1)
+            .exists();
+        folder.remove_all().unwrap();
+        assert!(saved);
+        assert!(tab.unsaved_ids.is_empty());
+        assert_eq!(tab.save_error, None);
+    }
+
+    #[test]
+    fn adding_a_taken_id_is_refused() {
+        let folder = test_folder("duplicate-id");
+        write_task(&folder, 1, "Buy milk", false);
+        let mut tab = task_tab(&folder);
+        // as if the ID counter was behind the entries
+        tab.next_id = 1;
+        tab.keypress(TERMINAL, KeyEvent::Char('n')).unwrap();
+        for c in "Call bob".chars() {
+            tab.keypress(TERMINAL, KeyEvent::Char(c)).unwrap();
+        }
+        tab.keypress(TERMINAL, KeyEvent::Ctrl('s')).unwrap();
+        let draft = fs::read_to_string(entry::GooseberryEntryType::Task.draft_path(&folder));
+        folder.remove_all().unwrap();
+        assert_eq!(tab.entries.len(), 1);
+        assert_eq!(tab.entries[&1].title(), "Buy milk");
+        assert_eq!(tab.visible_ids, vec![1]);
+        assert!(tab.save_error.unwrap().contains("already a Task with ID 1"));
+        // the text isn't lost, and the next try gets a free ID
+        assert!(draft.unwrap().contains("Call bob"));
+        assert_eq!(tab.next_id, 2);
+    }
+
+    #[test]
+    fn deleted_max_id_is_not_reused_after_a_restart() {
+        let folder = test_folder("deleted-max-id");
+        for id in 1..=5 {
+            write_task(&folder, id, "Buy milk", false);
+        }
+        let mut tab = task_tab(&folder);
+        tab.delete_entry(5).unwrap();
+        let mut tab = task_tab(&folder);
+        let next_id = tab.next_id;
+        tab.keypress(TERMINAL, KeyEvent::Char('n')).unwrap();
+        for c in "Call bob".chars() {
+            tab.keypress(TERMINAL, KeyEvent::Char(c)).unwrap();
+        }
+        tab.keypress(TERMINAL, KeyEvent::Ctrl('s')).unwrap();
+        let tab = task_tab(&folder);
+        folder.remove_all().unwrap();
+        assert_eq!(next_id, 6);
+        assert!(!tab.entries.contains_key(&5));
+        assert_eq!(tab.entries[&6].title(), "Call bob");
+        assert_eq!(tab.next_id, 7);
+    }
+
+    #[test]
+    fn times_without_a_day_stay_on_the_day_of_the_edited_event() {
+        let folder = test_folder("edit-event-times");
+        write_entry(
+            &folder,
+            entry::GooseberryEntryType::Event,
+            1,
+            3,
+            "",
+            "Title: Seminar\nPeople: Alice\n",
+            "",
+        );
+        let mut tab =
+            GooseberryTab::from_folder(entry::GooseberryEntryType::Event, &folder, false).unwrap();
+        tab.selected_entry = 1;
+        tab.start_editing().unwrap();
+        // to the Start box, then the End box
+        for (box_index, time) in [(5, "13:00"), (6, "3:30 PM")] {
+            while tab.input_boxes.index() < box_index {
+                tab.keypress(TERMINAL, KeyEvent::Ctrl('n')).unwrap();
+            }
+            for c in time.chars() {
+                tab.keypress(TERMINAL, KeyEvent::Char(c)).unwrap();
+            }
+        }
+        tab.keypress(TERMINAL, KeyEvent::Ctrl('s')).unwrap();
+        let saved = fs::read_to_string(entry::GooseberryEntryType::Event.file_path(&folder, 1));
+        folder.remove_all().unwrap();
+        assert_eq!(tab.save_error, None);
+        let saved = saved.unwrap();
+        assert!(saved.contains("DateTime: 2020-01-03T10:00:00Z\n"));
+        assert!(saved.contains("Start: 2020-01-03T13:00:00Z\n"));
+        assert!(saved.contains("End: 2020-01-03T15:30:00Z\n"));
+    }
 }
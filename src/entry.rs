@@ -1,53 +1,303 @@
-use std::{collections::HashMap, fmt, iter::Peekable, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, fs,
+    io::{self, Write},
+    iter::Peekable,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
-use anyhow::Error;
-use chrono::{Date, DateTime, NaiveDateTime, Utc};
-use path_abs::{PathDir, PathFile, PathOps};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat, Utc};
+use path_abs::{PathDir, PathFile};
 use tui::widgets::Text;
 
-use crate::errors::Sorry;
+use crate::errors::{GooseberryError, Sorry};
+use crate::query::ListOrder;
+use crate::utility::config::CONFIG;
 use crate::utility::{
     self,
+    formatting::{BodyFeatures, DisplayFormat, DisplayMode},
     interactive::{InputBox, InputBoxes},
 };
 
 /// Enum to list the entry types
-/// Adding a new kind of entry seems needlessly complicated now
-/// TODO: Make it so that you only have to add a new struct and a line to the GooseberryEntry enum to add a new entry type
-#[derive(Copy, Debug, Clone, PartialEq, Eq)]
+/// To add a new kind of entry: a struct implementing `GooseberryEntryTrait`, a variant here, in `ALL`,
+/// `Display`, `get_input_boxes`, `get_append_box` and `GooseberryEntry`, and an arm in `with_entry!`
+/// and `with_entry_type!`
+#[derive(Copy, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum GooseberryEntryType {
     Task,
     Research,
     Journal,
     Event,
+    Habit,
+}
+
+impl GooseberryEntryType {
+    /// Every entry type there is
+    pub const ALL: [GooseberryEntryType; 5] = [
+        GooseberryEntryType::Task,
+        GooseberryEntryType::Research,
+        GooseberryEntryType::Journal,
+        GooseberryEntryType::Event,
+        GooseberryEntryType::Habit,
+    ];
+}
+
+/// Runs `$body` with `$e` bound to the entry inside a `GooseberryEntry`, whatever its type
+macro_rules! with_entry {
+    ($g_entry:expr, $e:ident => $body:expr) => {
+        match $g_entry {
+            GooseberryEntry::Task($e) => $body,
+            GooseberryEntry::Journal($e) => $body,
+            GooseberryEntry::Research($e) => $body,
+            GooseberryEntry::Event($e) => $body,
+            GooseberryEntry::Habit($e) => $body,
+        }
+    };
+}
+
+/// Runs `$body` with `$entry` naming the struct for an entry type
+/// and `$variant` the `GooseberryEntry` variant that holds it
+macro_rules! with_entry_type {
+    ($entry_type:expr, $entry:ident, $variant:ident => $body:expr) => {
+        match $entry_type {
+            GooseberryEntryType::Task => {
+                use self::TaskEntry as $entry;
+                let $variant = GooseberryEntry::Task;
+                $body
+            }
+            GooseberryEntryType::Journal => {
+                use self::JournalEntry as $entry;
+                let $variant = GooseberryEntry::Journal;
+                $body
+            }
+            GooseberryEntryType::Research => {
+                use self::ResearchEntry as $entry;
+                let $variant = GooseberryEntry::Research;
+                $body
+            }
+            GooseberryEntryType::Event => {
+                use self::EventEntry as $entry;
+                let $variant = GooseberryEntry::Event;
+                $body
+            }
+            GooseberryEntryType::Habit => {
+                use self::HabitEntry as $entry;
+                let $variant = GooseberryEntry::Habit;
+                $body
+            }
+        }
+    };
 }
 
-/// formats and creates a file to save an entry
-/// <entry_type>_<entry_id>.md
+/// Placeholder for the entry type in `CONFIG.entry_file_pattern`
+pub const TYPE_PLACEHOLDER: &str = "{type}";
+/// Placeholder for the entry ID in `CONFIG.entry_file_pattern`
+pub const ID_PLACEHOLDER: &str = "{id}";
+
+/// Subfolder of the entries folder that archived entries are moved to (and not loaded from)
+pub const ARCHIVE_FOLDER: &str = "archive";
+
+/// Entry files are named by `CONFIG.entry_file_pattern`, <entry_type>_<entry_id>.md by default
 impl GooseberryEntryType {
-    pub fn get_file(self, folder: &PathDir, id: u64) -> Result<PathFile, Error> {
-        Ok(PathFile::create(
-            folder.join(&format!("{}_{}.md", self, id)),
-        )?)
+    /// Path of an entry's file relative to the entries folder
+    fn file_name(self, id: &str) -> String {
+        CONFIG
+            .entry_file_pattern
+            .replace(TYPE_PLACEHOLDER, &self.to_string())
+            .replace(ID_PLACEHOLDER, id)
+    }
+
+    /// Where an entry of this type with this ID lives (doesn't check or create anything)
+    pub fn file_path(self, folder: &PathDir, id: u64) -> PathBuf {
+        folder.as_path().join(self.file_name(&id.to_string()))
+    }
+
+    /// Where an archived entry's file goes, the same path but in `ARCHIVE_FOLDER`
+    pub fn archive_path(self, folder: &PathDir, id: u64) -> PathBuf {
+        folder
+            .as_path()
+            .join(ARCHIVE_FOLDER)
+            .join(self.file_name(&id.to_string()))
+    }
+
+    /// Where an unfinished new entry of this type is kept between runs
+    pub fn draft_path(self, folder: &PathDir) -> PathBuf {
+        folder.as_path().join(format!(".{}_draft", self))
+    }
+
+    /// Where the next ID to give a new entry of this type is kept,
+    /// so IDs of deleted entries aren't given out again
+    pub fn next_id_path(self, folder: &PathDir) -> PathBuf {
+        folder.as_path().join(format!(".{}_next_id", self))
+    }
+
+    /// The next ID to give a new entry: after every stored, loaded and archived one
+    pub fn next_id(self, folder: &PathDir, loaded_ids: &[u64]) -> u64 {
+        let stored = PathFile::new(self.next_id_path(folder))
+            .and_then(|file| file.read_string())
+            .ok()
+            .and_then(|next_id| next_id.trim().parse().ok())
+            .unwrap_or(0);
+        let archive_folder = PathDir::new(folder.as_path().join(ARCHIVE_FOLDER));
+        let archived = archive_folder
+            .ok()
+            .and_then(|archive_folder| {
+                let files = glob::glob(&self.glob_pattern(&archive_folder)).ok()?;
+                files
+                    .filter_map(Result::ok)
+                    .filter_map(|file| self.id_from_file(&archive_folder, &file))
+                    .max()
+            })
+            .unwrap_or(0);
+        let loaded = loaded_ids.iter().copied().max().unwrap_or(0);
+        stored.max(archived + 1).max(loaded + 1)
+    }
+
+    /// Makes sure saving entry `id` won't overwrite a different entry
+    /// (the file for `id` either doesn't exist yet or has `id` in its header)
+    pub fn check_file_id(self, folder: &PathDir, id: u64) -> Result<(), GooseberryError> {
+        let path = self.file_path(folder, id);
+        if !path.exists() {
+            return Ok(());
+        }
+        // a file without a readable header (e.g. left empty by a failed write) can't be an entry
+        let header = match get_header_lines(&PathFile::new(&path)?) {
+            Ok((header, _)) => header,
+            Err(_) => return Ok(()),
+        };
+        match header.get("ID").map(|other_id| other_id.trim().parse::<u64>()) {
+            Some(Ok(other_id)) if other_id != id => Err(Sorry::PathCollision {
+                entry_type: self,
+                entry_id: id,
+                other_id,
+                path: path.display().to_string(),
+            }
+            .into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// formats and creates a file to save an entry (and any folders it's in)
+    pub fn get_file(self, folder: &PathDir, id: u64) -> Result<PathFile, GooseberryError> {
+        let file = self.file_path(folder, id);
+        if let Some(parent) = AsRef::<Path>::as_ref(&file).parent() {
+            PathDir::create_all(parent)?;
+        }
+        Ok(PathFile::create(file)?)
+    }
+
+    /// Glob pattern matching all the files of this entry type in a folder
+    pub fn glob_pattern(self, folder: &PathDir) -> String {
+        format!("{}/{}", folder.as_path().display(), self.file_name("*"))
+    }
+
+    /// Gets the ID from the path of an entry file, None if the path doesn't follow the pattern
+    pub fn id_from_file(self, folder: &PathDir, file: &Path) -> Option<u64> {
+        let file_name = file.strip_prefix(folder.as_path()).ok()?.to_str()?;
+        let pattern = self.file_name(ID_PLACEHOLDER);
+        let id_start = pattern.find(ID_PLACEHOLDER)?;
+        let (prefix, suffix) = (
+            &pattern[..id_start],
+            &pattern[id_start + ID_PLACEHOLDER.len()..],
+        );
+        file_name
+            .strip_prefix(prefix)?
+            .strip_suffix(suffix)?
+            .parse()
+            .ok()
     }
 }
 
-/// For reading the entry type from the markdown metadata
-impl FromStr for GooseberryEntryType {
-    type Err = Error;
+/// Writes to a hidden `.<name>.tmp` file next to `path` and renames it over `path`,
+/// so a crash halfway through leaves either the old file or the new one, never half of it
+pub fn write_atomically(path: &Path, contents: &str) -> io::Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no file name to write to"))?;
+    let temp_path = path.with_file_name(format!(".{}.tmp", file_name.to_string_lossy()));
+    let written = fs::File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(contents.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&temp_path, path));
+    if written.is_err() {
+        fs::remove_file(&temp_path).ok();
+    }
+    written
+}
 
-    fn from_str(s: &str) -> Result<GooseberryEntryType, Error> {
-        match s.trim() {
-            "Task" => Ok(GooseberryEntryType::Task),
-            "Research" => Ok(GooseberryEntryType::Research),
-            "Journal" => Ok(GooseberryEntryType::Journal),
-            "Event" => Ok(GooseberryEntryType::Event),
-            _ => Err(Sorry::UnknownEntryType {
-                entry_type: s.to_owned(),
+/// Names of all the entry types with files in a folder, including ones gooseberry doesn't know
+/// A file counts if its name follows `CONFIG.entry_file_pattern` with a numeric ID
+pub fn entry_type_names(folder: &PathDir) -> Result<Vec<String>, GooseberryError> {
+    let pattern = &CONFIG.entry_file_pattern;
+    let (type_start, id_start) =
+        match (pattern.find(TYPE_PLACEHOLDER), pattern.find(ID_PLACEHOLDER)) {
+            (Some(type_start), Some(id_start)) => (type_start, id_start),
+            _ => return Ok(Vec::new()),
+        };
+    let type_first = type_start < id_start;
+    let (first_end, second_start, second_end) = if type_first {
+        (type_start + TYPE_PLACEHOLDER.len(), id_start, id_start + ID_PLACEHOLDER.len())
+    } else {
+        (id_start + ID_PLACEHOLDER.len(), type_start, type_start + TYPE_PLACEHOLDER.len())
+    };
+    let prefix = &pattern[..type_start.min(id_start)];
+    let separator = &pattern[first_end..second_start];
+    let suffix = &pattern[second_end..];
+    let any_file = pattern
+        .replace(TYPE_PLACEHOLDER, "*")
+        .replace(ID_PLACEHOLDER, "*");
+    let mut names = Vec::new();
+    for file in glob::glob(&format!("{}/{}", folder.as_path().display(), any_file))? {
+        let file = file?;
+        let middle = file
+            .strip_prefix(folder.as_path())
+            .ok()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix(prefix))
+            .and_then(|name| name.strip_suffix(suffix));
+        // the ID is a number, so it's the type that might have the separator in it
+        let parts = middle.and_then(|middle| {
+            if type_first {
+                middle
+                    .rfind(separator)
+                    .map(|i| (&middle[..i], &middle[i + separator.len()..]))
+            } else {
+                middle
+                    .find(separator)
+                    .map(|i| (&middle[i + separator.len()..], &middle[..i]))
+            }
+        });
+        if let Some((name, id)) = parts {
+            if !name.is_empty() && id.parse::<u64>().is_ok() && !names.iter().any(|n| n == name)
+            {
+                names.push(name.to_owned());
             }
-                .into()),
         }
     }
+    Ok(names)
+}
+
+/// For reading the entry type from the markdown metadata
+impl FromStr for GooseberryEntryType {
+    type Err = GooseberryError;
+
+    /// Same name as it's displayed
+    fn from_str(s: &str) -> Result<GooseberryEntryType, GooseberryError> {
+        GooseberryEntryType::ALL
+            .iter()
+            .find(|entry_type| entry_type.to_string() == s.trim())
+            .cloned()
+            .ok_or_else(|| {
+                Sorry::UnknownEntryType {
+                    entry_type: s.to_owned(),
+                }
+                .into()
+            })
+    }
 }
 
 /// For displaying the tabs on the Tab bar
@@ -58,6 +308,7 @@ impl fmt::Display for GooseberryEntryType {
             GooseberryEntryType::Journal => write!(f, "Journal"),
             GooseberryEntryType::Research => write!(f, "Research"),
             GooseberryEntryType::Event => write!(f, "Event"),
+            GooseberryEntryType::Habit => write!(f, "Habit"),
         }
     }
 }
@@ -65,43 +316,84 @@ impl fmt::Display for GooseberryEntryType {
 /// Trait to make a new kind of Entry type
 pub trait GooseberryEntryTrait: Sized {
     /// Gets metadata from header and main description/notes content from lines
-    fn from_header_lines(header: HashMap<String, String>, lines: String) -> Result<Self, Error>;
+    fn from_header_lines(header: HashMap<String, String>, lines: String) -> Result<Self, GooseberryError>;
     /// Converts text input boxes to entry (This is a bit hacky and assumes an order for the boxes)
     fn from_input_boxes(
         id: u64,
         entry_type: GooseberryEntryType,
         boxes: Vec<InputBox>,
-    ) -> Result<Self, Error>;
+    ) -> Result<Self, GooseberryError>;
     /// Puts contents of the entry into respective text input boxes for editing
     fn to_input_boxes(&self) -> InputBoxes;
     fn id(&self) -> u64;
     fn tags(&self) -> &[String];
     fn datetime(&self) -> &DateTime<Utc>;
+    fn order(&self) -> Option<i64>;
+    /// Sets (or clears) the manual list position
+    fn set_order(&mut self, order: Option<i64>);
     fn entry_type(&self) -> GooseberryEntryType;
-    /// Writes to file
-    fn to_file(&self, filename: PathFile) -> Result<(), Error>;
-    /// Styles entry for short display (in fold mode)
-    fn to_tui_short(&self, terminal_width: u16) -> Result<Vec<Text>, Error>;
+    /// What the entry is called: the task, the title, or the first line of a Journal entry
+    fn title(&self) -> &str;
+    /// The written content of an entry (title/task, description/notes, people), for searching
+    fn texts(&self) -> Vec<&str>;
+    /// The description/notes, what `A <id>` appends to
+    fn body_mut(&mut self) -> &mut String;
+    /// Reads an entry from the contents of a markdown file
+    fn from_markdown_string(markdown: &str) -> Result<Self, GooseberryError> {
+        let (header, lines) = split_header_lines(markdown)?;
+        Self::from_header_lines(header, lines)
+    }
+    /// What gets written to file (header + description/notes)
+    fn to_markdown_string(&self) -> String;
+    /// Writes to file, see `write_atomically`
+    fn to_file(&self, filename: PathFile) -> Result<(), GooseberryError> {
+        write_atomically(filename.as_path(), &self.to_markdown_string())?;
+        Ok(())
+    }
+    /// Styles entry for short display (in fold and compact mode)
+    fn to_tui_short(&self, format: DisplayFormat) -> Result<Vec<Text<'_>>, GooseberryError>;
     /// Styles entry for full display
-    fn to_tui_long(&self, terminal_width: u16) -> Result<Vec<Text>, Error>;
+    fn to_tui_long(&self, format: DisplayFormat) -> Result<Vec<Text<'_>>, GooseberryError>;
     fn merge_with_entry(&mut self, old_entry: &Self);
+    /// Everything wrong with the entry that would stop it from being saved and read back as it is
+    fn validate(&self) -> Result<(), Vec<String>> {
+        problems_to_result(common_problems(self))
+    }
     /// This metadata is common for all entries
+    /// Order is only written if the entry was moved by hand
     fn format_id_datetime_tags(&self) -> String {
+        let order = match self.order() {
+            Some(order) => format!("\nOrder: {}", order),
+            None => String::new(),
+        };
         format!(
-            "Type: {}\nID: {}\nDateTime: {}\nTags: {}",
+            "Type: {}\nID: {}\nDateTime: {}\nTags: {}{}",
             self.entry_type(),
             self.id(),
-            // TODO: change this to %c later after clearing entries (also the parsing part)
-            self.datetime().format("%v %r"),
-            self.tags()
-                .iter()
-                .map(|t| t.to_string())
-                .collect::<Vec<_>>()
-                .join(", ")
+            format_header_datetime(self.datetime()),
+            format_tags(self.tags()),
+            order
         )
     }
 }
 
+/// Which entries of a tab are listed and how, see `GooseberryEntry::entries_to_styled_blocks_same_type`
+#[derive(Debug, Clone, Copy)]
+pub struct ListView<'a> {
+    pub visible_ids: &'a [u64],
+    /// get a marker
+    pub unsaved_ids: &'a HashSet<u64>,
+    /// shown short even in Full mode
+    pub collapsed_ids: &'a HashSet<u64>,
+    pub display_mode: DisplayMode,
+    pub list_order: ListOrder,
+    /// true => Events and Tasks are grouped by day, see `agenda_blocks`
+    pub agenda: bool,
+}
+
+/// The texts of each entry in display order, with the entry's ID (None for things like date headers)
+pub type StyledBlocks<'a> = Vec<(Option<u64>, Vec<Text<'a>>)>;
+
 #[derive(Debug, Clone)]
 pub enum GooseberryEntry {
     /// Tasks/todos with an attached description
@@ -112,24 +404,120 @@ pub enum GooseberryEntry {
     Research(ResearchEntry),
     /// Meetings/Conferences etc. with other people/presenters
     Event(EventEntry),
+    /// Things to do regularly, with how many times in a row they've been done
+    Habit(HabitEntry),
 }
 
 impl GooseberryEntry {
-    pub fn from_file(filename: &PathFile) -> Result<Self, Error> {
-        let (header, lines) = get_header_lines(filename)?;
-        Self::from_header_lines(header, lines)
+    pub fn from_file(filename: &PathFile) -> Result<Self, GooseberryError> {
+        Self::from_markdown_string(&filename.read_string()?)
+    }
+
+    /// true for Tasks that are done, false for everything else
+    pub fn is_done(&self) -> bool {
+        match self {
+            GooseberryEntry::Task(e) => e.done,
+            _ => false,
+        }
+    }
+
+    /// How important a Task is, `Priority::None` for the other types
+    pub fn priority(&self) -> Priority {
+        match self {
+            GooseberryEntry::Task(e) => e.priority,
+            _ => Priority::None,
+        }
+    }
+
+    /// Where the entry goes in the agenda view: an Event's start or a Task's due date
+    pub fn agenda_datetime(&self) -> Option<DateTime<Utc>> {
+        match self {
+            GooseberryEntry::Event(e) => Some(e.start()),
+            GooseberryEntry::Task(e) => e.due,
+            _ => None,
+        }
+    }
+
+    /// Over with: an Event that's ended (or started, if it has no end) or a done Task
+    pub fn is_past(&self) -> bool {
+        match self {
+            GooseberryEntry::Event(e) => e.end.unwrap_or_else(|| e.start()) < Utc::now(),
+            GooseberryEntry::Task(e) => e.done,
+            _ => false,
+        }
+    }
+
+    /// Adds text to the end of the description/notes on a new line
+    /// Title, tags and everything else stay as they are
+    pub fn append_to_body(&mut self, text: &str) {
+        let body = self.body_mut();
+        if !body.is_empty() && !body.ends_with('\n') {
+            body.push('\n');
+        }
+        body.push_str(text);
     }
 
     /// Retrieves styled texts to display for a dict of entries with the same type
     pub fn entries_to_styled_texts_same_type<'a>(
         entries: &'a HashMap<u64, Self>,
-        visible_ids: &'a [u64],
-        fold: bool,
+        view: ListView,
+        terminal_width: u16,
+    ) -> Result<Vec<Text<'a>>, GooseberryError> {
+        Ok(Self::entries_to_styled_blocks_same_type(
+            entries,
+            view,
+            terminal_width,
+        )?
+        .into_iter()
+        .flat_map(|(_, texts)| texts.into_iter())
+        .collect())
+    }
+
+    /// Same as `entries_to_styled_texts_same_type` but keeps the texts of each entry together
+    /// in display order, with the entry's ID (None for things like Journal date headers)
+    /// Adds `CONFIG.entry_divider` lines between entries if it's set (except in compact mode)
+    pub fn entries_to_styled_blocks_same_type<'a>(
+        entries: &'a HashMap<u64, Self>,
+        view: ListView,
+        terminal_width: u16,
+    ) -> Result<StyledBlocks<'a>, GooseberryError> {
+        let mut styled_blocks = Self::styled_blocks_same_type(
+            entries,
+            view,
+            terminal_width,
+        )?;
+        if let Some(divider) = CONFIG.entry_divider {
+            if view.display_mode != DisplayMode::Compact {
+                let last_entry = styled_blocks.iter().rposition(|(id, _)| id.is_some());
+                for (_, texts) in styled_blocks
+                    .iter_mut()
+                    .take(last_entry.unwrap_or(0))
+                    .filter(|(id, _)| id.is_some())
+                {
+                    texts.push(utility::formatting::style_divider(divider, terminal_width));
+                }
+            }
+        }
+        Ok(styled_blocks)
+    }
+
+    /// Styled texts of each entry (and Journal date header) in display order
+    /// Journal entries are grouped by day, days go in the direction of `list_order`
+    /// `agenda` => Events and Tasks are grouped by day too, see `agenda_blocks`
+    fn styled_blocks_same_type<'a>(
+        entries: &'a HashMap<u64, Self>,
+        view: ListView,
         terminal_width: u16,
-    ) -> Result<Vec<Text<'a>>, Error> {
-        let mut keys = visible_ids.to_vec();
-        keys.sort_by(|a, b| entries[a].datetime().cmp(entries[b].datetime()));
+    ) -> Result<StyledBlocks<'a>, GooseberryError> {
+        if view.visible_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut keys = view.visible_ids.to_vec();
+        keys.sort_by(|a, b| view.list_order.compare(&entries[a], &entries[b]));
         let entry_type = entries[&keys[0]].entry_type();
+        let format = DisplayFormat::new(terminal_width, entries.keys().max().cloned().unwrap_or(0))
+            .with_compact(view.display_mode == DisplayMode::Compact)
+            .with_metadata_order(CONFIG.metadata_orders.for_type(entry_type));
         if !entries.values().all(|e| e.entry_type() == entry_type) {
             return Err(Sorry::OutOfCheeseError {
                 message: "Expected entries of the same type".into(),
@@ -137,21 +525,9 @@ impl GooseberryEntry {
                 .into());
         }
         match entry_type {
-            GooseberryEntryType::Event
-            | GooseberryEntryType::Task
-            | GooseberryEntryType::Research => Ok(keys
-                .iter()
-                .map(|key| {
-                    if fold {
-                        entries[key].to_tui_short(terminal_width)
-                    } else {
-                        entries[key].to_tui_long(terminal_width)
-                    }
-                })
-                .collect::<Result<Vec<_>, Error>>()?
-                .into_iter()
-                .flat_map(|x| x.into_iter())
-                .collect()),
+            GooseberryEntryType::Event | GooseberryEntryType::Task if view.agenda => {
+                Self::agenda_blocks(entries, &keys, format, view, terminal_width)
+            }
             GooseberryEntryType::Journal => {
                 let mut dates_to_entries = HashMap::new();
                 for key in keys {
@@ -168,270 +544,540 @@ impl GooseberryEntry {
                             .into());
                     }
                 }
-                let mut styled_texts = Vec::new();
+                let mut styled_blocks = Vec::new();
                 let mut dates = dates_to_entries.keys().cloned().collect::<Vec<_>>();
                 dates.sort();
+                if view.list_order.descending {
+                    dates.reverse();
+                }
                 for date in dates {
                     let entries = dates_to_entries.get(&date);
                     if let Some(entries) = entries {
-                        styled_texts.push(utility::formatting::style_date_num_entries(
-                            date,
-                            entries.len(),
-                            terminal_width,
+                        styled_blocks.push((
+                            None,
+                            vec![utility::formatting::style_date_num_entries(
+                                date,
+                                entries.len(),
+                                terminal_width,
+                            )],
                         ));
-                        if !fold {
-                            //                            styled_texts.push(Text::Raw("\n".into()));
-                            for entry in entries {
-                                styled_texts.extend_from_slice(&entry.to_tui_long(terminal_width)?);
+                        for entry in entries {
+                            let format = format.with_unsaved(view.unsaved_ids.contains(&entry.id));
+                            match view.display_mode {
+                                DisplayMode::Full if !view.collapsed_ids.contains(&entry.id) => {
+                                    styled_blocks.push((Some(entry.id), entry.to_tui_long(format)?))
+                                }
+                                DisplayMode::Full | DisplayMode::Compact => {
+                                    styled_blocks.push((Some(entry.id), entry.to_tui_short(format)?))
+                                }
+                                DisplayMode::Fold => (),
                             }
                         }
                     }
                 }
-                Ok(styled_texts)
+                Ok(styled_blocks)
+            }
+            _ => keys
+                .iter()
+                .map(|key| {
+                    let format = format.with_unsaved(view.unsaved_ids.contains(key));
+                    let texts = match view.display_mode {
+                        DisplayMode::Full if !view.collapsed_ids.contains(key) => {
+                            entries[key].to_tui_long(format)
+                        }
+                        _ => entries[key].to_tui_short(format),
+                    }?;
+                    Ok((Some(*key), texts))
+                })
+                .collect(),
+        }
+    }
+
+    /// Events (and Tasks with due dates) grouped by day under a date header, in time order within a day
+    /// Tasks without a due date are left out, past Events and done Tasks are dimmed
+    fn agenda_blocks<'a>(
+        entries: &'a HashMap<u64, Self>,
+        keys: &[u64],
+        format: DisplayFormat,
+        view: ListView,
+        terminal_width: u16,
+    ) -> Result<StyledBlocks<'a>, GooseberryError> {
+        let mut dates_to_keys = HashMap::new();
+        for key in keys {
+            if let Some(datetime) = entries[key].agenda_datetime() {
+                dates_to_keys
+                    .entry(datetime.date_naive())
+                    .or_insert_with(Vec::new)
+                    .push((datetime, *key));
+            }
+        }
+        let mut dates = dates_to_keys.keys().cloned().collect::<Vec<_>>();
+        dates.sort();
+        if view.list_order.descending {
+            dates.reverse();
+        }
+        let mut styled_blocks = Vec::new();
+        for date in dates {
+            let mut day = dates_to_keys.remove(&date).unwrap_or_default();
+            day.sort();
+            styled_blocks.push((
+                None,
+                vec![utility::formatting::style_date_num_entries(
+                    date,
+                    day.len(),
+                    terminal_width,
+                )],
+            ));
+            for (_, key) in day {
+                let g_entry = &entries[&key];
+                let format = format.with_unsaved(view.unsaved_ids.contains(&key));
+                let texts = match view.display_mode {
+                    DisplayMode::Full if !view.collapsed_ids.contains(&key) => g_entry.to_tui_long(format),
+                    _ => g_entry.to_tui_short(format),
+                }?;
+                if g_entry.is_past() {
+                    styled_blocks.push((Some(key), utility::formatting::dim(texts)));
+                } else {
+                    styled_blocks.push((Some(key), texts));
+                }
             }
         }
+        Ok(styled_blocks)
     }
+
 }
 
-/// This was a bit annoying - just calls the underlying variant's trait method for each trait method
+/// Calls the underlying variant's trait method for each trait method
 impl GooseberryEntryTrait for GooseberryEntry {
-    fn from_header_lines(header: HashMap<String, String>, lines: String) -> Result<Self, Error> {
-        let entry_type = (&header.get("Type").ok_or(Sorry::MissingHeaderElement {
-            element: "Type".into(),
-        })?)
-            .parse::<GooseberryEntryType>()?;
-        match entry_type {
-            GooseberryEntryType::Task => Ok(GooseberryEntry::Task(TaskEntry::from_header_lines(
-                header, lines,
-            )?)),
-            GooseberryEntryType::Research => Ok(GooseberryEntry::Research(
-                ResearchEntry::from_header_lines(header, lines)?,
-            )),
-            GooseberryEntryType::Journal => Ok(GooseberryEntry::Journal(
-                JournalEntry::from_header_lines(header, lines)?,
-            )),
-            GooseberryEntryType::Event => Ok(GooseberryEntry::Event(
-                EventEntry::from_header_lines(header, lines)?,
-            )),
-        }
+    fn from_header_lines(header: HashMap<String, String>, lines: String) -> Result<Self, GooseberryError> {
+        let entry_type = match (header.get("Type"), CONFIG.default_entry_type) {
+            (Some(entry_type), _) => entry_type.parse::<GooseberryEntryType>()?,
+            (None, Some(entry_type)) => {
+                warn!("No Type in the header, reading it as a {}", entry_type);
+                entry_type
+            }
+            (None, None) => {
+                return Err(Sorry::MissingHeaderElement {
+                    element: "Type".into(),
+                }
+                .into())
+            }
+        };
+        with_entry_type!(entry_type, Entry, variant => {
+            Entry::from_header_lines(header, lines).map(variant)
+        })
     }
+
     fn from_input_boxes(
         id: u64,
         entry_type: GooseberryEntryType,
         boxes: Vec<InputBox>,
-    ) -> Result<Self, Error> {
-        match entry_type {
-            GooseberryEntryType::Task => Ok(GooseberryEntry::Task(TaskEntry::from_input_boxes(
-                id, entry_type, boxes,
-            )?)),
-            GooseberryEntryType::Journal => Ok(GooseberryEntry::Journal(
-                JournalEntry::from_input_boxes(id, entry_type, boxes)?,
-            )),
-            GooseberryEntryType::Event => Ok(GooseberryEntry::Event(EventEntry::from_input_boxes(
-                id, entry_type, boxes,
-            )?)),
-            GooseberryEntryType::Research => Ok(GooseberryEntry::Research(
-                ResearchEntry::from_input_boxes(id, entry_type, boxes)?,
-            )),
-        }
+    ) -> Result<Self, GooseberryError> {
+        with_entry_type!(entry_type, Entry, variant => {
+            Entry::from_input_boxes(id, entry_type, boxes).map(variant)
+        })
     }
 
     fn to_input_boxes(&self) -> InputBoxes {
-        match self {
-            GooseberryEntry::Task(e) => e.to_input_boxes(),
-            GooseberryEntry::Journal(e) => e.to_input_boxes(),
-            GooseberryEntry::Event(e) => e.to_input_boxes(),
-            GooseberryEntry::Research(e) => e.to_input_boxes(),
-        }
+        with_entry!(self, e => e.to_input_boxes())
     }
 
     fn id(&self) -> u64 {
-        match self {
-            GooseberryEntry::Task(e) => e.id(),
-            GooseberryEntry::Journal(e) => e.id(),
-            GooseberryEntry::Event(e) => e.id(),
-            GooseberryEntry::Research(e) => e.id(),
-        }
+        with_entry!(self, e => e.id())
     }
 
     fn tags(&self) -> &[String] {
-        match self {
-            GooseberryEntry::Task(e) => e.tags(),
-            GooseberryEntry::Journal(e) => e.tags(),
-            GooseberryEntry::Event(e) => e.tags(),
-            GooseberryEntry::Research(e) => e.tags(),
-        }
+        with_entry!(self, e => e.tags())
     }
 
     fn datetime(&self) -> &DateTime<Utc> {
-        match self {
-            GooseberryEntry::Task(e) => e.datetime(),
-            GooseberryEntry::Journal(e) => e.datetime(),
-            GooseberryEntry::Event(e) => e.datetime(),
-            GooseberryEntry::Research(e) => e.datetime(),
-        }
+        with_entry!(self, e => e.datetime())
+    }
+
+    fn order(&self) -> Option<i64> {
+        with_entry!(self, e => e.order())
+    }
+
+    fn set_order(&mut self, order: Option<i64>) {
+        with_entry!(self, e => e.set_order(order))
     }
 
     fn entry_type(&self) -> GooseberryEntryType {
-        match self {
-            GooseberryEntry::Task(e) => e.entry_type(),
-            GooseberryEntry::Journal(e) => e.entry_type(),
-            GooseberryEntry::Event(e) => e.entry_type(),
-            GooseberryEntry::Research(e) => e.entry_type(),
-        }
+        with_entry!(self, e => e.entry_type())
     }
 
-    fn to_file(&self, filename: PathFile) -> Result<(), Error> {
-        match self {
-            GooseberryEntry::Task(e) => e.to_file(filename),
-            GooseberryEntry::Journal(e) => e.to_file(filename),
-            GooseberryEntry::Event(e) => e.to_file(filename),
-            GooseberryEntry::Research(e) => e.to_file(filename),
-        }
+    fn title(&self) -> &str {
+        with_entry!(self, e => e.title())
     }
 
-    fn to_tui_short(&self, terminal_width: u16) -> Result<Vec<Text>, Error> {
-        match self {
-            GooseberryEntry::Task(e) => e.to_tui_short(terminal_width),
-            GooseberryEntry::Journal(e) => e.to_tui_short(terminal_width),
-            GooseberryEntry::Event(e) => e.to_tui_short(terminal_width),
-            GooseberryEntry::Research(e) => e.to_tui_short(terminal_width),
-        }
+    fn texts(&self) -> Vec<&str> {
+        with_entry!(self, e => e.texts())
     }
 
-    fn to_tui_long(&self, terminal_width: u16) -> Result<Vec<Text>, Error> {
-        match self {
-            GooseberryEntry::Task(e) => e.to_tui_long(terminal_width),
-            GooseberryEntry::Journal(e) => e.to_tui_long(terminal_width),
-            GooseberryEntry::Event(e) => e.to_tui_long(terminal_width),
-            GooseberryEntry::Research(e) => e.to_tui_long(terminal_width),
-        }
+    fn body_mut(&mut self) -> &mut String {
+        with_entry!(self, e => e.body_mut())
+    }
+
+    fn to_markdown_string(&self) -> String {
+        with_entry!(self, e => e.to_markdown_string())
+    }
+
+    fn to_tui_short(&self, format: DisplayFormat) -> Result<Vec<Text<'_>>, GooseberryError> {
+        with_entry!(self, e => e.to_tui_short(format))
+    }
+
+    fn to_tui_long(&self, format: DisplayFormat) -> Result<Vec<Text<'_>>, GooseberryError> {
+        with_entry!(self, e => e.to_tui_long(format))
+    }
+
+    fn validate(&self) -> Result<(), Vec<String>> {
+        with_entry!(self, e => e.validate())
     }
 
+    /// Does nothing if the old entry is of a different type
     fn merge_with_entry(&mut self, old_entry: &Self) {
-        match self {
-            GooseberryEntry::Task(e) => {
-                if let GooseberryEntry::Task(o) = old_entry {
-                    e.merge_with_entry(o)
-                }
-            }
-            GooseberryEntry::Journal(e) => {
-                if let GooseberryEntry::Journal(o) = old_entry {
-                    e.merge_with_entry(o)
-                }
-            }
-            GooseberryEntry::Event(e) => {
-                if let GooseberryEntry::Event(o) = old_entry {
-                    e.merge_with_entry(o)
-                }
-            }
-            GooseberryEntry::Research(e) => {
-                if let GooseberryEntry::Research(o) = old_entry {
-                    e.merge_with_entry(o)
-                }
-            }
+        match (self, old_entry) {
+            (GooseberryEntry::Task(e), GooseberryEntry::Task(o)) => e.merge_with_entry(o),
+            (GooseberryEntry::Journal(e), GooseberryEntry::Journal(o)) => e.merge_with_entry(o),
+            (GooseberryEntry::Research(e), GooseberryEntry::Research(o)) => e.merge_with_entry(o),
+            (GooseberryEntry::Event(e), GooseberryEntry::Event(o)) => e.merge_with_entry(o),
+            (GooseberryEntry::Habit(e), GooseberryEntry::Habit(o)) => e.merge_with_entry(o),
+            _ => (),
         }
     }
 }
 
 /// Reads metadata from markdown into a HashMap
+/// Header lines are `Element: value`, blank lines are skipped
+/// `Element:` without a space after the colon is read as an empty value
 fn consume_markdown_header<'a>(
     lines: &mut Peekable<impl Iterator<Item = &'a str>>,
-) -> Result<HashMap<String, String>, Error> {
-    if lines.next().unwrap() != utility::formatting::HEADER_MARK {
-        Err(Sorry::MissingHeader.into())
-    } else {
-        let mut header_lines = Vec::new();
-        loop {
-            if let Some(line) = lines.peek() {
-                if line == &utility::formatting::HEADER_MARK {
-                    lines.next().unwrap();
-                    break;
-                }
-            }
-            header_lines.push(lines.next().unwrap());
+) -> Result<HashMap<String, String>, GooseberryError> {
+    if lines.next() != Some(utility::formatting::HEADER_MARK) {
+        return Err(Sorry::MissingHeader.into());
+    }
+    let mut header = HashMap::new();
+    loop {
+        let line = lines.next().ok_or(Sorry::UnclosedHeader)?;
+        if line == utility::formatting::HEADER_MARK {
+            break;
         }
-        Ok(header_lines
-            .into_iter()
-            .map(|line| {
-                let parts = line.split(": ").collect::<Vec<_>>();
-                (parts[0].to_owned(), parts[1].to_owned())
-            })
-            .collect())
+        if line.trim().is_empty() {
+            continue;
+        }
+        let colon = line.find(':').ok_or_else(|| Sorry::MalformedHeaderLine {
+            line: line.to_owned(),
+        })?;
+        let value = &line[colon + 1..];
+        header.insert(
+            line[..colon].to_owned(),
+            value.strip_prefix(' ').unwrap_or(value).to_owned(),
+        );
     }
+    Ok(header)
 }
 
 impl GooseberryEntryType {
     /// Gets the text input boxes for each entry type along with their desired percentages
+    /// The boxes that make up an entry's title have to be filled in, the rest can stay empty
     /// Too hard-coded, this
     pub fn get_input_boxes(self) -> InputBoxes {
         match self {
             GooseberryEntryType::Task => InputBoxes::new(vec![
-                InputBox::new(String::from("Task"), false, 10),
-                InputBox::new(String::from("Description"), true, 60),
+                InputBox::new(String::from("Task"), false, 10).required(),
+                InputBox::new(String::from("Description"), true, 40),
                 InputBox::new(String::from("Tags"), false, 10),
+                InputBox::new(String::from("Due"), false, 10)
+                    .with_check(|due| parse_due(due).map(|_| ()).map_err(|e| e.to_string())),
+                InputBox::new(String::from("Priority"), false, 10).with_check(|priority| {
+                    priority
+                        .parse::<Priority>()
+                        .map(|_| ())
+                        .map_err(|e| e.to_string())
+                }),
             ]),
             GooseberryEntryType::Journal => InputBoxes::new(vec![
-                InputBox::new(String::from("Description"), false, 10),
+                InputBox::new(String::from("Description"), false, 10).required(),
                 InputBox::new(String::from("Tags"), false, 10),
             ]),
             GooseberryEntryType::Research => InputBoxes::new(vec![
-                InputBox::new(String::from("Title"), false, 10),
+                InputBox::new(String::from("Title"), false, 10).required(),
                 InputBox::new(String::from("Notes"), true, 60),
                 InputBox::new(String::from("Tags"), false, 10),
             ]),
             GooseberryEntryType::Event => InputBoxes::new(vec![
-                InputBox::new(String::from("Title"), false, 10),
+                InputBox::new(String::from("Title"), false, 10).required(),
+                InputBox::new(String::from("Notes"), true, 20),
+                InputBox::new(String::from("People"), true, 10),
+                InputBox::new(String::from("Location"), false, 10),
+                InputBox::new(String::from("Tags"), false, 10),
+                InputBox::new(String::from("Start"), false, 10).with_check(|start| {
+                    parse_start(start, &Utc::now())
+                        .map(|_| ())
+                        .map_err(|e| e.to_string())
+                }),
+                InputBox::new(String::from("End"), false, 10).with_check(|end| {
+                    parse_end(end, &Utc::now())
+                        .map(|_| ())
+                        .map_err(|e| e.to_string())
+                }),
+            ]),
+            GooseberryEntryType::Habit => InputBoxes::new(vec![
+                InputBox::new(String::from("Habit"), false, 10).required(),
+                InputBox::new(String::from("Streak"), false, 10).with_check(|streak| {
+                    match streak.trim() {
+                        "" => Ok(()),
+                        streak => streak.parse::<u64>().map(|_| ()).map_err(|e| e.to_string()),
+                    }
+                }),
                 InputBox::new(String::from("Notes"), true, 50),
-                InputBox::new(String::from("People"), false, 10),
                 InputBox::new(String::from("Tags"), false, 10),
             ]),
         }
     }
+
+    /// The single box used to append to an entry's description/notes
+    pub fn get_append_box(self) -> InputBoxes {
+        let (title, markdown) = match self {
+            GooseberryEntryType::Task => ("Append to Description", true),
+            GooseberryEntryType::Journal => ("Append to Description", false),
+            GooseberryEntryType::Research
+            | GooseberryEntryType::Event
+            | GooseberryEntryType::Habit => ("Append to Notes", true),
+        };
+        InputBoxes::new(vec![InputBox::new(String::from(title), markdown, 60)])
+    }
 }
 
 /// Splits a markdown file into the metadata and the content
-pub fn get_header_lines(filename: &PathFile) -> Result<(HashMap<String, String>, String), Error> {
-    let content = filename.read_string()?;
+pub fn get_header_lines(filename: &PathFile) -> Result<(HashMap<String, String>, String), GooseberryError> {
+    split_header_lines(&filename.read_string()?)
+}
+
+/// Splits the contents of a markdown file into the header and everything after it
+pub fn split_header_lines(content: &str) -> Result<(HashMap<String, String>, String), GooseberryError> {
     let mut lines = content.split('\n').peekable();
     let header = consume_markdown_header(&mut lines)?;
     let lines: String = lines.collect::<Vec<_>>().join("\n");
     Ok((header, lines))
 }
 
+/// Splits a comma-separated list of tags
+/// A tag in double quotes can have commas in it, e.g. `"a,b", c` gives `a,b` and `c`
+/// Empty tags are dropped and repeated ones kept once, e.g. `work, , urgent, work,` gives `work` and `urgent`
+pub fn parse_tags(tags: &str) -> Vec<String> {
+    let mut parsed = Vec::new();
+    let mut rest = tags;
+    loop {
+        let trimmed = rest.trim_start();
+        if let Some(quoted) = trimmed.strip_prefix('"') {
+            if let Some(end) = quoted.find('"') {
+                parsed.push(quoted[..end].to_owned());
+                // anything between the closing quote and the next comma is ignored
+                let after_quote = &quoted[end + 1..];
+                match after_quote.find(',') {
+                    Some(comma) => rest = &after_quote[comma + 1..],
+                    None => break,
+                }
+                continue;
+            }
+        }
+        match rest.find(',') {
+            Some(comma) => {
+                parsed.push(rest[..comma].trim().to_owned());
+                rest = &rest[comma + 1..];
+            }
+            None => {
+                parsed.push(rest.trim().to_owned());
+                break;
+            }
+        }
+    }
+    let mut seen = HashSet::new();
+    parsed.retain(|tag| !tag.is_empty() && seen.insert(tag.clone()));
+    parsed
+}
+
+/// Opposite of `parse_tags`, quotes tags with commas in them
+fn format_tags(tags: &[String]) -> String {
+    tags.iter()
+        .map(|t| {
+            if t.contains(',') {
+                format!("\"{}\"", t)
+            } else {
+                t.to_owned()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// How datetimes were written in headers before RFC 3339, e.g. ` 1-Oct-2026 10:00:00 AM`
+const OLD_HEADER_DATETIME_FORMAT: &str = "%v %r";
+
+/// Writes a datetime for a header as RFC 3339, e.g. `2026-10-01T10:00:00Z`
+fn format_header_datetime(datetime: &DateTime<Utc>) -> String {
+    datetime.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Reads a datetime from a header, RFC 3339 or the format older files have (`OLD_HEADER_DATETIME_FORMAT`)
+fn parse_header_datetime(text: &str) -> Result<DateTime<Utc>, GooseberryError> {
+    let text = text.trim();
+    match DateTime::parse_from_rfc3339(text) {
+        Ok(datetime) => Ok(datetime.with_timezone(&Utc)),
+        Err(_) => Ok(DateTime::from_naive_utc_and_offset(
+            NaiveDateTime::parse_from_str(text, OLD_HEADER_DATETIME_FORMAT)?,
+            Utc,
+        )),
+    }
+}
+
 /// Gets the ID, DateTime, and tags from a markdown header
 fn get_id_datetime_tags(
     header: &HashMap<String, String>,
-) -> Result<(u64, DateTime<Utc>, Vec<String>), Error> {
+) -> Result<(u64, DateTime<Utc>, Vec<String>), GooseberryError> {
     let id = header
         .get("ID")
         .ok_or(Sorry::MissingHeaderElement {
             element: "ID".into(),
         })?
         .parse::<u64>()?;
-    let datetime = DateTime::from_utc(
-        NaiveDateTime::parse_from_str(
-            header
-                .get("DateTime")
-                .ok_or(Sorry::MissingHeaderElement {
-                    element: "DateTime".into(),
-                })?
-                .trim(),
-            "%v %r",
-        )?,
-        Utc,
-    );
-    let tags = header
-        .get("Tags")
-        .ok_or(Sorry::MissingHeaderElement {
-            element: "Tags".into(),
-        })?
-        .split(',')
-        .map(|t| t.trim().to_owned())
-        .collect::<Vec<_>>();
+    let datetime = parse_header_datetime(header.get("DateTime").ok_or(
+        Sorry::MissingHeaderElement {
+            element: "DateTime".into(),
+        },
+    )?)?;
+    let tags = parse_tags(header.get("Tags").ok_or(Sorry::MissingHeaderElement {
+        element: "Tags".into(),
+    })?);
     Ok((id, datetime, tags))
 }
 
+/// Gets the manual list position from a markdown header, None if it's not there
+fn get_order(header: &HashMap<String, String>) -> Result<Option<i64>, GooseberryError> {
+    match header.get("Order") {
+        Some(order) => Ok(Some(order.trim().parse::<i64>()?)),
+        None => Ok(None),
+    }
+}
+
+/// A header DateTime that can be left out or empty, like `Due:` or `End:`
+fn get_optional_datetime(
+    header: &HashMap<String, String>,
+    element: &str,
+) -> Result<Option<DateTime<Utc>>, GooseberryError> {
+    match header.get(element).map(|datetime| datetime.trim()) {
+        Some(datetime) if !datetime.is_empty() => Ok(Some(parse_header_datetime(datetime)?)),
+        _ => Ok(None),
+    }
+}
+
+/// Reads a typed-in due date, e.g. `2026-10-31 17:00`, `2026-10-31T17:00:00Z` or `31-Oct-2026 05:00:00 PM`
+/// A day without a time is due at the end of that day
+pub fn parse_due(text: &str) -> Result<DateTime<Utc>, GooseberryError> {
+    let text = text.trim();
+    if let Ok(due) = DateTime::parse_from_rfc3339(text) {
+        return Ok(due.with_timezone(&Utc));
+    }
+    for format in &["%Y-%m-%d %H:%M", "%v %r", "%v %H:%M"] {
+        if let Ok(due) = NaiveDateTime::parse_from_str(text, format) {
+            return Ok(DateTime::from_naive_utc_and_offset(due, Utc));
+        }
+    }
+    for format in &["%Y-%m-%d", "%v"] {
+        if let Some(due) = NaiveDate::parse_from_str(text, format)
+            .ok()
+            .and_then(|due| due.and_hms_opt(23, 59, 59))
+        {
+            return Ok(DateTime::from_naive_utc_and_offset(due, Utc));
+        }
+    }
+    Err(Sorry::InvalidDate { date: text.into() }.into())
+}
+
+/// Reads a typed-in Event end, either a time alone (`15:30` or `3:30 PM`) on the day it starts,
+/// or anything `parse_due` reads
+pub fn parse_end(text: &str, start: &DateTime<Utc>) -> Result<DateTime<Utc>, GooseberryError> {
+    let text = text.trim();
+    for format in &["%H:%M", "%I:%M %p", "%r"] {
+        if let Ok(time) = NaiveTime::parse_from_str(text, format) {
+            return Ok(DateTime::from_naive_utc_and_offset(
+                start.naive_utc().date().and_time(time),
+                Utc,
+            ));
+        }
+    }
+    parse_due(text)
+}
+
+/// Reads a typed-in Event start, a time alone is on the day of `day`
+/// and a day alone is at the start of that day, otherwise anything `parse_due` reads
+pub fn parse_start(text: &str, day: &DateTime<Utc>) -> Result<DateTime<Utc>, GooseberryError> {
+    let text = text.trim();
+    for format in &["%Y-%m-%d", "%v"] {
+        if let Some(start) = NaiveDate::parse_from_str(text, format)
+            .ok()
+            .and_then(|start| start.and_hms_opt(0, 0, 0))
+        {
+            return Ok(DateTime::from_naive_utc_and_offset(start, Utc));
+        }
+    }
+    parse_end(text, day)
+}
+
+/// Problems any type of entry can have
+/// Tags can't have line breaks or double quotes (they wouldn't read back the same)
+/// and the DateTime has to fit the header format
+fn common_problems(g_entry: &impl GooseberryEntryTrait) -> Vec<String> {
+    let mut problems = Vec::new();
+    for tag in g_entry.tags() {
+        if tag.contains('\n') || tag.contains('"') {
+            problems.push(format!("Tag {:?} can't have line breaks or double quotes", tag));
+        }
+    }
+    let datetime = format_header_datetime(g_entry.datetime());
+    if parse_header_datetime(&datetime).is_err() {
+        problems.push(format!("DateTime {:?} can't be read back", datetime));
+    }
+    problems
+}
+
+fn problems_to_result(problems: Vec<String>) -> Result<(), Vec<String>> {
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems)
+    }
+}
+
+/// Header elements every entry type reads
+const COMMON_HEADER_ELEMENTS: [&str; 5] = ["Type", "ID", "DateTime", "Tags", "Order"];
+
+/// Gets the header elements that an entry type doesn't know about
+/// so they can be written back unchanged
+fn get_extra_header(
+    header: &HashMap<String, String>,
+    known_elements: &[&str],
+) -> HashMap<String, String> {
+    header
+        .iter()
+        .filter(|(key, _)| {
+            !COMMON_HEADER_ELEMENTS.contains(&key.as_str())
+                && !known_elements.contains(&key.as_str())
+        })
+        .map(|(key, value)| (key.to_owned(), value.to_owned()))
+        .collect()
+}
+
+/// Formats extra header elements as `key: value` lines (sorted by key)
+fn format_extra_header(extra: &HashMap<String, String>) -> String {
+    let mut keys = extra.keys().collect::<Vec<_>>();
+    keys.sort();
+    keys.into_iter()
+        .map(|key| format!("{}: {}\n", key, extra[key]))
+        .collect()
+}
+
 /// Entry type to store tasks/todos
 #[derive(Clone, Debug)]
 pub struct TaskEntry {
@@ -443,7 +1089,52 @@ pub struct TaskEntry {
     pub datetime: DateTime<Utc>,
     /// state of completion
     pub done: bool,
+    /// shown in `CONFIG.overdue_color` once it's passed and the task isn't done
+    pub due: Option<DateTime<Utc>>,
+    pub priority: Priority,
     pub tags: Vec<String>,
+    /// manual position in the list (lower goes first), None => listed by date
+    pub order: Option<i64>,
+    /// header elements gooseberry doesn't use, kept so they're not lost on save
+    pub extra: HashMap<String, String>,
+}
+
+/// How important a Task is, in the order they're sorted
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    High,
+    Medium,
+    Low,
+    None,
+}
+
+/// Doesn't care about case, and takes the first letter (or `med`) as well
+impl FromStr for Priority {
+    type Err = GooseberryError;
+
+    fn from_str(s: &str) -> Result<Priority, GooseberryError> {
+        match s.trim().to_lowercase().as_str() {
+            "high" | "h" => Ok(Priority::High),
+            "medium" | "med" | "m" => Ok(Priority::Medium),
+            "low" | "l" => Ok(Priority::Low),
+            "none" | "" => Ok(Priority::None),
+            _ => Err(Sorry::UnknownPriority {
+                priority: s.to_owned(),
+            }
+            .into()),
+        }
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Priority::High => write!(f, "High"),
+            Priority::Medium => write!(f, "Medium"),
+            Priority::Low => write!(f, "Low"),
+            Priority::None => write!(f, "None"),
+        }
+    }
 }
 
 impl TaskEntry {
@@ -454,7 +1145,7 @@ impl TaskEntry {
 
 impl GooseberryEntryTrait for TaskEntry {
     /// Extra metadata - the task and the task state
-    fn from_header_lines(header: HashMap<String, String>, lines: String) -> Result<Self, Error> {
+    fn from_header_lines(header: HashMap<String, String>, lines: String) -> Result<Self, GooseberryError> {
         let (id, datetime, tags) = get_id_datetime_tags(&header)?;
         let task = header
             .get("Task")
@@ -470,22 +1161,41 @@ impl GooseberryEntryTrait for TaskEntry {
             })?
             .trim()
             .parse::<bool>()?;
+        // a priority that can't be read is kept as it is (in `extra`) instead of failing the whole entry
+        let priority = match header.get("Priority").map(|p| p.parse::<Priority>()) {
+            Some(Ok(priority)) => Some(priority),
+            Some(Err(error)) => {
+                warn!("Task {}: {}", id, error);
+                None
+            }
+            None => Some(Priority::None),
+        };
+        let known_elements: &[&str] = if priority.is_some() {
+            &["Task", "Done", "Due", "Priority"]
+        } else {
+            &["Task", "Done", "Due"]
+        };
         Ok(TaskEntry {
             id,
             task,
             description: lines,
             datetime,
             done,
+            due: get_optional_datetime(&header, "Due")?,
+            priority: priority.unwrap_or(Priority::None),
             tags,
+            order: get_order(&header)?,
+            extra: get_extra_header(&header, known_elements),
         })
     }
 
-    /// Assumes that the first box has the task, the second has the description, and the third has tags
+    /// Assumes that the first box has the task, the second has the description, the third has tags,
+    /// the fourth has the due date and the fifth the priority (both can be empty)
     fn from_input_boxes(
         id: u64,
         entry_type: GooseberryEntryType,
         boxes: Vec<InputBox>,
-    ) -> Result<Self, Error> {
+    ) -> Result<Self, GooseberryError> {
         if entry_type != GooseberryEntryType::Task {
             return Err(Sorry::WrongEntryType {
                 expected: GooseberryEntryType::Task,
@@ -494,28 +1204,39 @@ impl GooseberryEntryTrait for TaskEntry {
                 .into());
         }
         let (task, description) = (boxes[0].get_content(), boxes[1].get_content());
-        let tags = boxes[2]
-            .get_content()
-            .split(',')
-            .map(|t| t.trim().to_owned())
-            .collect();
+        let tags = parse_tags(&boxes[2].get_content());
+        let due = match boxes[3].get_content().trim() {
+            "" => None,
+            due => Some(parse_due(due)?),
+        };
+        let priority = boxes[4].get_content().parse::<Priority>()?;
         Ok(TaskEntry {
             id,
             task,
             description,
             datetime: Utc::now(),
             done: false,
+            due,
+            priority,
             tags,
+            order: None,
+            extra: HashMap::new(),
         })
     }
 
-    /// Puts the contents into three text input boxes: task, description, and tags
+    /// Puts the contents into five text input boxes: task, description, tags, due date, and priority
     fn to_input_boxes(&self) -> InputBoxes {
         let mut input_boxes = self.entry_type().get_input_boxes();
         input_boxes.replace_content(0, &self.task);
         input_boxes.replace_content(1, &self.description);
-        input_boxes.replace_content(2, &self.tags.join(", "));
-        input_boxes
+        input_boxes.replace_content(2, &format_tags(&self.tags));
+        if let Some(due) = self.due {
+            input_boxes.replace_content(3, &due.format("%Y-%m-%d %H:%M").to_string());
+        }
+        if self.priority != Priority::None {
+            input_boxes.replace_content(4, &self.priority.to_string());
+        }
+        input_boxes
     }
 
     fn id(&self) -> u64 {
@@ -530,48 +1251,88 @@ impl GooseberryEntryTrait for TaskEntry {
         &self.datetime
     }
 
+    fn order(&self) -> Option<i64> {
+        self.order
+    }
+
     fn entry_type(&self) -> GooseberryEntryType {
         GooseberryEntryType::Task
     }
 
-    fn to_file(&self, filename: PathFile) -> Result<(), Error> {
+    fn set_order(&mut self, order: Option<i64>) {
+        self.order = order;
+    }
+
+    fn title(&self) -> &str {
+        &self.task
+    }
+
+    fn texts(&self) -> Vec<&str> {
+        vec![&self.task, &self.description]
+    }
+
+    fn body_mut(&mut self) -> &mut String {
+        &mut self.description
+    }
+
+    fn to_markdown_string(&self) -> String {
         let header = format!(
-            "{}\n{}\nTask: {}\nDone: {}\n{}\n",
+            "{}\n{}\nTask: {}\nDone: {}\n{}{}{}{}\n",
             utility::formatting::HEADER_MARK,
             self.format_id_datetime_tags(),
             self.task,
             self.done,
+            self.due
+                .map(|due| format!("Due: {}\n", format_header_datetime(&due)))
+                .unwrap_or_default(),
+            match self.priority {
+                Priority::None => String::new(),
+                priority => format!("Priority: {}\n", priority),
+            },
+            format_extra_header(&self.extra),
             utility::formatting::HEADER_MARK,
         );
-        filename.write_str(&format!("{}{}", header, self.description))?;
-        Ok(())
+        format!("{}{}", header, self.description)
     }
 
     /// Puts the task state symbol in between the ID and the task
-    fn to_tui_short(&self, terminal_width: u16) -> Result<Vec<Text>, Error> {
+    /// Tasks that aren't done are colored by how close their due date is, with the due date under the metadata,
+    /// otherwise by priority
+    fn to_tui_short(&self, format: DisplayFormat) -> Result<Vec<Text<'_>>, GooseberryError> {
         let mark = if self.done {
             utility::formatting::TaskState::Done
         } else {
             utility::formatting::TaskState::NotDone
         };
-        Ok(utility::formatting::style_short(
+        let due_color = match self.due {
+            Some(due) if !self.done => utility::formatting::deadline_color(&due),
+            _ => None,
+        };
+        let format = format.with_title_color(
+            due_color.or_else(|| CONFIG.priority_colors.for_priority(self.priority)),
+        );
+        let mut styled_text = utility::formatting::style_short(
             self.id,
             &self.task,
             Some(mark),
             &self.datetime,
             &self.tags,
-            terminal_width,
-            false,
-            false,
-            true,
-        ))
+            &[],
+            format
+                .with_body_features(BodyFeatures::from_markdown(&self.description))
+                .with_title_style(CONFIG.title_styles.for_type(self.entry_type())),
+        );
+        if let (Some(due), false) = (self.due, format.compact) {
+            styled_text.push(utility::formatting::style_due(due, due_color));
+        }
+        Ok(styled_text)
     }
 
     /// Adds the description to the short version
-    fn to_tui_long(&self, terminal_width: u16) -> Result<Vec<Text>, Error> {
-        let mut styled_text = self.to_tui_short(terminal_width)?;
+    fn to_tui_long(&self, format: DisplayFormat) -> Result<Vec<Text<'_>>, GooseberryError> {
+        let mut styled_text = self.to_tui_short(format)?;
         styled_text.extend_from_slice(&utility::formatting::markdown_to_styled_texts(
-            &self.description.trim(),
+            self.description.trim(),
         ));
         styled_text.push(Text::Raw("\n".into()));
         styled_text.push(Text::Raw("\n".into()));
@@ -581,7 +1342,22 @@ impl GooseberryEntryTrait for TaskEntry {
     fn merge_with_entry(&mut self, old_entry: &Self) {
         self.id = old_entry.id;
         self.datetime = old_entry.datetime;
+        self.extra = old_entry.extra.clone();
+        self.order = old_entry.order;
         self.done = old_entry.done;
+        // an unreadable priority kept from the file is replaced by one from the input box
+        if self.priority != Priority::None {
+            self.extra.remove("Priority");
+        }
+    }
+
+    /// The task can't be empty
+    fn validate(&self) -> Result<(), Vec<String>> {
+        let mut problems = common_problems(self);
+        if self.task.trim().is_empty() {
+            problems.push("The task is empty".into());
+        }
+        problems_to_result(problems)
     }
 }
 
@@ -593,26 +1369,50 @@ pub struct JournalEntry {
     pub description: String,
     pub datetime: DateTime<Utc>,
     pub tags: Vec<String>,
+    /// manual position in the list (lower goes first), None => listed by date
+    pub order: Option<i64>,
+    /// header elements gooseberry doesn't use, kept so they're not lost on save
+    pub extra: HashMap<String, String>,
+}
+
+/// Which day a Journal entry is listed under
+#[derive(Copy, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum JournalGrouping {
+    /// the day it was written
+    Created,
+    /// the day in its `EventDate` header (e.g. `EventDate: 1-Oct-2026`), the day it was written if there isn't one
+    EventDate,
 }
 
 impl JournalEntry {
-    /// Use this to group entries by day and only show the day once
-    /// No idea how yet
-    /// Probably have to move the entry printing loop as a function of GooseberryEntry (add to trait)
-    fn date(&self) -> Date<Utc> {
-        self.datetime.date()
+    /// The day the entry is grouped under, see `CONFIG.journal_grouping`
+    fn date(&self) -> NaiveDate {
+        match CONFIG.journal_grouping {
+            JournalGrouping::Created => self.datetime.date_naive(),
+            JournalGrouping::EventDate => self
+                .event_date()
+                .unwrap_or_else(|| self.datetime.date_naive()),
+        }
+    }
+
+    /// The day from the `EventDate` header, None if it's missing or can't be read
+    fn event_date(&self) -> Option<NaiveDate> {
+        let date = self.extra.get("EventDate")?;
+        NaiveDate::parse_from_str(date.trim(), "%v").ok()
     }
 }
 
 impl GooseberryEntryTrait for JournalEntry {
     /// No extra metadata
-    fn from_header_lines(header: HashMap<String, String>, lines: String) -> Result<Self, Error> {
+    fn from_header_lines(header: HashMap<String, String>, lines: String) -> Result<Self, GooseberryError> {
         let (id, datetime, tags) = get_id_datetime_tags(&header)?;
         Ok(JournalEntry {
             id,
             description: lines,
             datetime,
             tags,
+            order: get_order(&header)?,
+            extra: get_extra_header(&header, &[]),
         })
     }
 
@@ -622,7 +1422,7 @@ impl GooseberryEntryTrait for JournalEntry {
         id: u64,
         entry_type: GooseberryEntryType,
         boxes: Vec<InputBox>,
-    ) -> Result<Self, Error> {
+    ) -> Result<Self, GooseberryError> {
         if entry_type != GooseberryEntryType::Journal {
             return Err(Sorry::WrongEntryType {
                 expected: GooseberryEntryType::Journal,
@@ -631,17 +1431,15 @@ impl GooseberryEntryTrait for JournalEntry {
                 .into());
         }
         let description = boxes[0].get_content();
-        let tags = boxes[1]
-            .get_content()
-            .split(',')
-            .map(|t| t.trim().to_owned())
-            .collect();
+        let tags = parse_tags(&boxes[1].get_content());
 
         Ok(JournalEntry {
             id,
             description,
             datetime: Utc::now(),
             tags,
+            order: None,
+            extra: HashMap::new(),
         })
     }
 
@@ -650,7 +1448,7 @@ impl GooseberryEntryTrait for JournalEntry {
     fn to_input_boxes(&self) -> InputBoxes {
         let mut input_boxes = self.entry_type().get_input_boxes();
         input_boxes.replace_content(0, &self.description);
-        input_boxes.replace_content(1, &self.tags.join(", "));
+        input_boxes.replace_content(1, &format_tags(&self.tags));
         input_boxes
     }
 
@@ -666,38 +1464,62 @@ impl GooseberryEntryTrait for JournalEntry {
         &self.datetime
     }
 
+    fn order(&self) -> Option<i64> {
+        self.order
+    }
+
     fn entry_type(&self) -> GooseberryEntryType {
         GooseberryEntryType::Journal
     }
 
-    fn to_file(&self, filename: PathFile) -> Result<(), Error> {
+    fn set_order(&mut self, order: Option<i64>) {
+        self.order = order;
+    }
+
+    /// The first line of the description
+    fn title(&self) -> &str {
+        self.description.lines().next().unwrap_or_default()
+    }
+
+    fn texts(&self) -> Vec<&str> {
+        vec![&self.description]
+    }
+
+    fn body_mut(&mut self) -> &mut String {
+        &mut self.description
+    }
+
+    fn to_markdown_string(&self) -> String {
         let header = format!(
-            "{}\n{}\n{}\n",
+            "{}\n{}\n{}{}\n",
             utility::formatting::HEADER_MARK,
             self.format_id_datetime_tags(),
+            format_extra_header(&self.extra),
             utility::formatting::HEADER_MARK
         );
-        filename.write_str(&format!("{}{}", header, self.description))?;
-        Ok(())
+        format!("{}{}", header, self.description)
     }
 
     /// Short and long return the same thing
-    fn to_tui_short(&self, terminal_width: u16) -> Result<Vec<Text>, Error> {
+    /// The description is rendered as markdown if `CONFIG.markdown_journal` is set
+    fn to_tui_short(&self, format: DisplayFormat) -> Result<Vec<Text<'_>>, GooseberryError> {
         Ok(utility::formatting::style_short(
             self.id,
             &self.description,
             None,
             &self.datetime,
             &self.tags,
-            terminal_width,
-            false,
-            true,
-            false,
+            &[],
+            format
+                .with_markdown_title(CONFIG.markdown_journal)
+                .with_body_features(BodyFeatures::from_markdown(&self.description))
+                .with_title_style(CONFIG.title_styles.for_type(self.entry_type()))
+                .with_time_only(),
         ))
     }
 
-    fn to_tui_long(&self, terminal_width: u16) -> Result<Vec<Text>, Error> {
-        let mut styled_text = self.to_tui_short(terminal_width)?;
+    fn to_tui_long(&self, format: DisplayFormat) -> Result<Vec<Text<'_>>, GooseberryError> {
+        let mut styled_text = self.to_tui_short(format)?;
         styled_text.push(Text::Raw("\n".into()));
         Ok(styled_text)
     }
@@ -705,6 +1527,17 @@ impl GooseberryEntryTrait for JournalEntry {
     fn merge_with_entry(&mut self, old_entry: &Self) {
         self.id = old_entry.id;
         self.datetime = old_entry.datetime;
+        self.extra = old_entry.extra.clone();
+        self.order = old_entry.order;
+    }
+
+    /// There has to be something written
+    fn validate(&self) -> Result<(), Vec<String>> {
+        let mut problems = common_problems(self);
+        if self.description.trim().is_empty() {
+            problems.push("The description is empty".into());
+        }
+        problems_to_result(problems)
     }
 }
 
@@ -717,11 +1550,15 @@ pub struct ResearchEntry {
     pub notes: String,
     pub datetime: DateTime<Utc>,
     pub tags: Vec<String>,
+    /// manual position in the list (lower goes first), None => listed by date
+    pub order: Option<i64>,
+    /// header elements gooseberry doesn't use, kept so they're not lost on save
+    pub extra: HashMap<String, String>,
 }
 
 impl GooseberryEntryTrait for ResearchEntry {
     /// Title extra
-    fn from_header_lines(header: HashMap<String, String>, lines: String) -> Result<Self, Error> {
+    fn from_header_lines(header: HashMap<String, String>, lines: String) -> Result<Self, GooseberryError> {
         let (id, datetime, tags) = get_id_datetime_tags(&header)?;
         let title = header
             .get("Title")
@@ -736,6 +1573,8 @@ impl GooseberryEntryTrait for ResearchEntry {
             notes: lines,
             datetime,
             tags,
+            order: get_order(&header)?,
+            extra: get_extra_header(&header, &["Title"]),
         })
     }
 
@@ -746,7 +1585,7 @@ impl GooseberryEntryTrait for ResearchEntry {
         id: u64,
         entry_type: GooseberryEntryType,
         boxes: Vec<InputBox>,
-    ) -> Result<Self, Error> {
+    ) -> Result<Self, GooseberryError> {
         if entry_type != GooseberryEntryType::Research {
             return Err(Sorry::WrongEntryType {
                 expected: GooseberryEntryType::Research,
@@ -755,17 +1594,15 @@ impl GooseberryEntryTrait for ResearchEntry {
                 .into());
         }
         let (title, notes) = (boxes[0].get_content(), boxes[1].get_content());
-        let tags = boxes[2]
-            .get_content()
-            .split(',')
-            .map(|t| t.trim().to_owned())
-            .collect();
+        let tags = parse_tags(&boxes[2].get_content());
         Ok(ResearchEntry {
             id,
             title,
             notes,
             datetime: Utc::now(),
             tags,
+            order: None,
+            extra: HashMap::new(),
         })
     }
 
@@ -776,7 +1613,7 @@ impl GooseberryEntryTrait for ResearchEntry {
         let mut input_boxes = self.entry_type().get_input_boxes();
         input_boxes.replace_content(0, &self.title);
         input_boxes.replace_content(1, &self.notes);
-        input_boxes.replace_content(2, &self.tags.join(", "));
+        input_boxes.replace_content(2, &format_tags(&self.tags));
         input_boxes
     }
 
@@ -792,45 +1629,66 @@ impl GooseberryEntryTrait for ResearchEntry {
         &self.datetime
     }
 
+    fn order(&self) -> Option<i64> {
+        self.order
+    }
+
     fn entry_type(&self) -> GooseberryEntryType {
         GooseberryEntryType::Research
     }
 
-    fn to_file(&self, filename: PathFile) -> Result<(), Error> {
+    fn set_order(&mut self, order: Option<i64>) {
+        self.order = order;
+    }
+
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn texts(&self) -> Vec<&str> {
+        vec![&self.title, &self.notes]
+    }
+
+    fn body_mut(&mut self) -> &mut String {
+        &mut self.notes
+    }
+
+    fn to_markdown_string(&self) -> String {
         let header = format!(
-            "{}\n{}\nTitle: {}\n{}\n",
+            "{}\n{}\nTitle: {}\n{}{}\n",
             utility::formatting::HEADER_MARK,
             self.format_id_datetime_tags(),
             self.title,
+            format_extra_header(&self.extra),
             utility::formatting::HEADER_MARK,
         );
-        filename.write_str(&format!("{}{}", header, self.notes))?;
-        Ok(())
+        format!("{}{}", header, self.notes)
     }
 
     /// ID Title
     /// DateTime
     /// Tags
-    fn to_tui_short(&self, terminal_width: u16) -> Result<Vec<Text>, Error> {
+    fn to_tui_short(&self, format: DisplayFormat) -> Result<Vec<Text<'_>>, GooseberryError> {
         Ok(utility::formatting::style_short(
             self.id,
             &self.title,
             None,
             &self.datetime,
             &self.tags,
-            terminal_width,
-            true,
-            false,
-            true,
+            &[],
+            format
+                .with_body_features(BodyFeatures::from_markdown(&self.notes))
+                .with_title_style(CONFIG.title_styles.for_type(self.entry_type()))
+                .with_date_only(),
         ))
     }
 
     /// Adds notes to short
-    fn to_tui_long(&self, terminal_width: u16) -> Result<Vec<Text>, Error> {
-        let mut styled_text = self.to_tui_short(terminal_width)?;
+    fn to_tui_long(&self, format: DisplayFormat) -> Result<Vec<Text<'_>>, GooseberryError> {
+        let mut styled_text = self.to_tui_short(format)?;
         styled_text.push(Text::Raw("\n".into()));
         styled_text.extend_from_slice(&utility::formatting::markdown_to_styled_texts(
-            &self.notes.trim(),
+            self.notes.trim(),
         ));
         styled_text.push(Text::Raw("\n".into()));
         styled_text.push(Text::Raw("\n".into()));
@@ -840,6 +1698,17 @@ impl GooseberryEntryTrait for ResearchEntry {
     fn merge_with_entry(&mut self, old_entry: &Self) {
         self.id = old_entry.id;
         self.datetime = old_entry.datetime;
+        self.extra = old_entry.extra.clone();
+        self.order = old_entry.order;
+    }
+
+    /// The title can't be empty
+    fn validate(&self) -> Result<(), Vec<String>> {
+        let mut problems = common_problems(self);
+        if self.title.trim().is_empty() {
+            problems.push("The title is empty".into());
+        }
+        problems_to_result(problems)
     }
 }
 
@@ -850,22 +1719,133 @@ pub struct EventEntry {
     /// Title of the talk/meeting description
     pub title: String,
     /// Who's involved/who's presenting
-    pub people: Vec<String>,
+    pub people: Vec<Person>,
+    /// Where it is, empty => not given
+    pub location: String,
     pub datetime: DateTime<Utc>,
+    /// when it starts, None => when it was written (`datetime`)
+    pub start: Option<DateTime<Utc>>,
+    /// when it finishes, None => no end time given
+    pub end: Option<DateTime<Utc>>,
     pub notes: String,
     pub tags: Vec<String>,
+    /// manual position in the list (lower goes first), None => listed by date
+    pub order: Option<i64>,
+    /// header elements gooseberry doesn't use, kept so they're not lost on save
+    pub extra: HashMap<String, String>,
+    /// Start and End as typed in, so `merge_with_entry` can put times without a day
+    /// on the day of the Event being edited instead of today
+    typed_times: Option<(String, String)>,
 }
 
 impl EventEntry {
+    /// When it starts, when it was written if no start was given
+    pub fn start(&self) -> DateTime<Utc> {
+        self.start.unwrap_or(self.datetime)
+    }
+
     /// How to display a list of people
     fn format_people(&self) -> String {
-        self.people.join(", ")
+        self.people
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Title, date and tags, and people (if given) in the configured order
+    fn style_metadata<'a>(
+        &'a self,
+        format: DisplayFormat,
+        people_by_role: &[(Option<&str>, Vec<&str>)],
+    ) -> Vec<Text<'a>> {
+        let format = format.with_title_color(utility::formatting::soon_color(&self.start()));
+        utility::formatting::style_short(
+            self.id,
+            &self.title,
+            None,
+            self.start.as_ref().unwrap_or(&self.datetime),
+            &self.tags,
+            people_by_role,
+            format
+                .with_body_features(BodyFeatures::from_markdown(&self.notes))
+                .with_title_style(CONFIG.title_styles.for_type(self.entry_type())),
+        )
+    }
+
+    /// People grouped by role, in order of first appearance
+    /// People without a role come first (with None)
+    fn people_by_role(&self) -> Vec<(Option<&str>, Vec<&str>)> {
+        let mut groups: Vec<(Option<&str>, Vec<&str>)> = vec![(None, Vec::new())];
+        for person in &self.people {
+            let role = person.role.as_deref();
+            match groups.iter_mut().find(|(r, _)| *r == role) {
+                Some((_, names)) => names.push(&person.name),
+                None => groups.push((role, vec![&person.name])),
+            }
+        }
+        groups.retain(|(_, names)| !names.is_empty());
+        groups
+    }
+}
+
+/// Someone at an Event, optionally with their role, written as `Name (role)`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Person {
+    pub name: String,
+    pub role: Option<String>,
+}
+
+impl Person {
+    /// `Alice (presenter)` => Alice with role presenter, `Bob` => Bob without a role
+    fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if text.is_empty() {
+            return None;
+        }
+        if text.ends_with(')') {
+            if let Some(open) = text.rfind('(') {
+                let (name, role) = (text[..open].trim(), text[open + 1..text.len() - 1].trim());
+                if !name.is_empty() {
+                    return Some(Person {
+                        name: name.to_owned(),
+                        role: if role.is_empty() {
+                            None
+                        } else {
+                            Some(role.to_owned())
+                        },
+                    });
+                }
+            }
+        }
+        Some(Person {
+            name: text.to_owned(),
+            role: None,
+        })
+    }
+}
+
+impl fmt::Display for Person {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.role {
+            Some(role) => write!(f, "{} ({})", self.name, role),
+            None => write!(f, "{}", self.name),
+        }
     }
 }
 
+/// Splits a list of people separated by commas or newlines
+/// An empty list (e.g. `People: `) gives no people instead of one empty name
+fn parse_people(people: &str) -> Vec<Person> {
+    people
+        .split([',', '\n'])
+        .filter_map(Person::parse)
+        .collect()
+}
+
 impl GooseberryEntryTrait for EventEntry {
-    /// Title and people are extra
-    fn from_header_lines(header: HashMap<String, String>, lines: String) -> Result<Self, Error> {
+    /// Title and people are extra, Location, Start and End lines are optional
+    fn from_header_lines(header: HashMap<String, String>, lines: String) -> Result<Self, GooseberryError> {
         let (id, datetime, tags) = get_id_datetime_tags(&header)?;
         let title = header
             .get("Title")
@@ -874,33 +1854,41 @@ impl GooseberryEntryTrait for EventEntry {
             })?
             .trim()
             .to_owned();
-        let people = header
-            .get("People")
-            .ok_or(Sorry::MissingHeaderElement {
-                element: "People".into(),
-            })?
-            .split(',')
-            .map(|p| p.trim().to_owned())
-            .collect();
+        let people = parse_people(header.get("People").ok_or(Sorry::MissingHeaderElement {
+            element: "People".into(),
+        })?);
+        let location = header
+            .get("Location")
+            .map(|location| location.trim().to_owned())
+            .unwrap_or_default();
         Ok(EventEntry {
             id,
             title,
             people,
+            location,
             datetime,
+            start: get_optional_datetime(&header, "Start")?,
+            end: get_optional_datetime(&header, "End")?,
             notes: lines,
             tags,
+            order: get_order(&header)?,
+            extra: get_extra_header(&header, &["Title", "People", "Location", "Start", "End"]),
+            typed_times: None,
         })
     }
 
     /// First box: title
     /// Second box: notes
     /// Third box: people
-    /// Fourth box: tags
+    /// Fourth box: location
+    /// Fifth box: tags
+    /// Sixth box: start (empty => when it's written)
+    /// Seventh box: end (empty => None)
     fn from_input_boxes(
         id: u64,
         entry_type: GooseberryEntryType,
         boxes: Vec<InputBox>,
-    ) -> Result<Self, Error> {
+    ) -> Result<Self, GooseberryError> {
         if entry_type != GooseberryEntryType::Event {
             return Err(Sorry::WrongEntryType {
                 expected: GooseberryEntryType::Event,
@@ -909,36 +1897,56 @@ impl GooseberryEntryTrait for EventEntry {
                 .into());
         }
         let (title, notes) = (boxes[0].get_content(), boxes[1].get_content());
-        let people = boxes[2]
-            .get_content()
-            .split(',')
-            .map(|t| t.trim().to_owned())
-            .collect();
-        let tags = boxes[3]
-            .get_content()
-            .split(',')
-            .map(|t| t.trim().to_owned())
-            .collect();
+        let people = parse_people(&boxes[2].get_content());
+        let location = boxes[3].get_content().trim().to_owned();
+        let tags = parse_tags(&boxes[4].get_content());
+        let datetime = Utc::now();
+        let typed_start = boxes[5].get_content().trim().to_owned();
+        let typed_end = boxes[6].get_content().trim().to_owned();
+        let start = match typed_start.as_str() {
+            "" => None,
+            start => Some(parse_start(start, &datetime)?),
+        };
+        let end = match typed_end.as_str() {
+            "" => None,
+            end => Some(parse_end(end, &start.unwrap_or(datetime))?),
+        };
         Ok(EventEntry {
             id,
             title,
             notes,
-            datetime: Utc::now(),
+            datetime,
+            start,
+            end,
             people,
+            location,
             tags,
+            order: None,
+            extra: HashMap::new(),
+            typed_times: Some((typed_start, typed_end)),
         })
     }
 
     /// First box: title
     /// Second box: notes
     /// Third box: people
-    /// Fourth box: tags
+    /// Fourth box: location
+    /// Fifth box: tags
+    /// Sixth box: start, and
+    /// Seventh box: end, both with their day so they stay put when edited on another day
     fn to_input_boxes(&self) -> InputBoxes {
         let mut input_boxes = self.entry_type().get_input_boxes();
         input_boxes.replace_content(0, &self.title);
         input_boxes.replace_content(1, &self.notes);
-        input_boxes.replace_content(2, &self.people.join(", "));
-        input_boxes.replace_content(3, &self.tags.join(", "));
+        input_boxes.replace_content(2, &self.format_people());
+        input_boxes.replace_content(3, &self.location);
+        input_boxes.replace_content(4, &format_tags(&self.tags));
+        if let Some(start) = self.start {
+            input_boxes.replace_content(5, &start.format("%Y-%m-%d %H:%M").to_string());
+        }
+        if let Some(end) = self.end {
+            input_boxes.replace_content(6, &end.format("%Y-%m-%d %H:%M").to_string());
+        }
         input_boxes
     }
 
@@ -954,50 +1962,297 @@ impl GooseberryEntryTrait for EventEntry {
         &self.datetime
     }
 
+    fn order(&self) -> Option<i64> {
+        self.order
+    }
+
     fn entry_type(&self) -> GooseberryEntryType {
         GooseberryEntryType::Event
     }
 
-    fn to_file(&self, filename: PathFile) -> Result<(), Error> {
+    fn set_order(&mut self, order: Option<i64>) {
+        self.order = order;
+    }
+
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Title, notes, location, and people's names and roles
+    fn texts(&self) -> Vec<&str> {
+        let mut texts: Vec<&str> = vec![&self.title, &self.notes, &self.location];
+        texts.extend(self.people.iter().map(|p| p.name.as_str()));
+        texts.extend(self.people.iter().filter_map(|p| p.role.as_deref()));
+        texts
+    }
+
+    fn body_mut(&mut self) -> &mut String {
+        &mut self.notes
+    }
+
+    fn to_markdown_string(&self) -> String {
         let header = format!(
-            "{}\n{}\nTitle: {}\nPeople: {}\n{}\n",
+            "{}\n{}\nTitle: {}\nPeople: {}\n{}{}{}{}{}\n",
             utility::formatting::HEADER_MARK,
             self.format_id_datetime_tags(),
             self.title,
             self.format_people(),
+            if self.location.is_empty() {
+                String::new()
+            } else {
+                format!("Location: {}\n", self.location)
+            },
+            self.start
+                .map(|start| format!("Start: {}\n", format_header_datetime(&start)))
+                .unwrap_or_default(),
+            self.end
+                .map(|end| format!("End: {}\n", format_header_datetime(&end)))
+                .unwrap_or_default(),
+            format_extra_header(&self.extra),
             utility::formatting::HEADER_MARK,
         );
-        filename.write_str(&format!("{}{}", header, self.notes))?;
-        Ok(())
+        format!("{}{}", header, self.notes)
     }
 
     /// ID Title
-    /// DateTime
+    /// Start (DateTime if it has none)
     /// tags
-    fn to_tui_short(&self, terminal_width: u16) -> Result<Vec<Text>, Error> {
-        Ok(utility::formatting::style_short(
+    fn to_tui_short(&self, format: DisplayFormat) -> Result<Vec<Text<'_>>, GooseberryError> {
+        Ok(self.style_metadata(format, &[]))
+    }
+
+    /// Short
+    /// People (if any), one line per role
+    /// Location (if given)
+    /// Start - end time (if there's an end)
+    ///
+    /// Notes
+    fn to_tui_long(&self, format: DisplayFormat) -> Result<Vec<Text<'_>>, GooseberryError> {
+        let mut styled_text = self.style_metadata(format, &self.people_by_role());
+        if !self.location.is_empty() {
+            styled_text.push(utility::formatting::style_field(
+                "Location",
+                self.location.clone(),
+                None,
+            ));
+        }
+        if let Some(end) = self.end {
+            styled_text.push(utility::formatting::style_time_range(self.start(), end));
+        }
+        styled_text.push(Text::Raw("\n".into()));
+        styled_text.extend_from_slice(&utility::formatting::markdown_to_styled_texts(
+            self.notes.trim(),
+        ));
+        styled_text.push(Text::Raw("\n".into()));
+        styled_text.push(Text::Raw("\n".into()));
+        Ok(styled_text)
+    }
+
+    /// Times typed without a day are read again on the day of the Event being edited,
+    /// they were read as today's in `from_input_boxes`
+    fn merge_with_entry(&mut self, old_entry: &Self) {
+        self.id = old_entry.id;
+        self.datetime = old_entry.datetime;
+        self.extra = old_entry.extra.clone();
+        self.order = old_entry.order;
+        if let Some((start, end)) = self.typed_times.take() {
+            if let Ok(start) = parse_start(&start, &old_entry.start()) {
+                self.start = Some(start);
+            }
+            if let Ok(end) = parse_end(&end, &self.start()) {
+                self.end = Some(end);
+            }
+        }
+    }
+
+    /// The title can't be empty, and neither can anyone's name
+    /// It can't end before it starts
+    fn validate(&self) -> Result<(), Vec<String>> {
+        let mut problems = common_problems(self);
+        if self.title.trim().is_empty() {
+            problems.push("The title is empty".into());
+        }
+        if self.end.is_some_and(|end| end < self.start()) {
+            problems.push("It ends before it starts".into());
+        }
+        if self.people.iter().any(|p| p.name.trim().is_empty()) {
+            problems.push("Someone in People has no name".into());
+        }
+        problems_to_result(problems)
+    }
+}
+
+/// Something to do regularly, e.g. exercise or reading
+#[derive(Clone, Debug)]
+pub struct HabitEntry {
+    pub id: u64,
+    /// Short one-liner on what the habit is
+    pub habit: String,
+    /// how many times in a row it's been kept up
+    pub streak: u64,
+    pub notes: String,
+    pub datetime: DateTime<Utc>,
+    pub tags: Vec<String>,
+    /// manual position in the list (lower goes first), None => listed by date
+    pub order: Option<i64>,
+    /// header elements gooseberry doesn't use, kept so they're not lost on save
+    pub extra: HashMap<String, String>,
+}
+
+impl GooseberryEntryTrait for HabitEntry {
+    /// Habit and streak extra, a missing streak is 0
+    fn from_header_lines(header: HashMap<String, String>, lines: String) -> Result<Self, GooseberryError> {
+        let (id, datetime, tags) = get_id_datetime_tags(&header)?;
+        let habit = header
+            .get("Habit")
+            .ok_or(Sorry::MissingHeaderElement {
+                element: "Habit".into(),
+            })?
+            .trim()
+            .to_owned();
+        let streak = match header.get("Streak") {
+            Some(streak) => streak.trim().parse::<u64>()?,
+            None => 0,
+        };
+        Ok(HabitEntry {
+            id,
+            habit,
+            streak,
+            notes: lines,
+            datetime,
+            tags,
+            order: get_order(&header)?,
+            extra: get_extra_header(&header, &["Habit", "Streak"]),
+        })
+    }
+
+    /// First box: habit
+    /// Second box: streak (empty => 0)
+    /// Third box: notes
+    /// Fourth box: tags
+    fn from_input_boxes(
+        id: u64,
+        entry_type: GooseberryEntryType,
+        boxes: Vec<InputBox>,
+    ) -> Result<Self, GooseberryError> {
+        if entry_type != GooseberryEntryType::Habit {
+            return Err(Sorry::WrongEntryType {
+                expected: GooseberryEntryType::Habit,
+                got: entry_type,
+            }
+            .into());
+        }
+        let streak = match boxes[1].get_content().trim() {
+            "" => 0,
+            streak => streak.parse::<u64>()?,
+        };
+        Ok(HabitEntry {
+            id,
+            habit: boxes[0].get_content(),
+            streak,
+            notes: boxes[2].get_content(),
+            datetime: Utc::now(),
+            tags: parse_tags(&boxes[3].get_content()),
+            order: None,
+            extra: HashMap::new(),
+        })
+    }
+
+    /// First box: habit
+    /// Second box: streak
+    /// Third box: notes
+    /// Fourth box: tags
+    fn to_input_boxes(&self) -> InputBoxes {
+        let mut input_boxes = self.entry_type().get_input_boxes();
+        input_boxes.replace_content(0, &self.habit);
+        input_boxes.replace_content(1, &self.streak.to_string());
+        input_boxes.replace_content(2, &self.notes);
+        input_boxes.replace_content(3, &format_tags(&self.tags));
+        input_boxes
+    }
+
+    fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    fn datetime(&self) -> &DateTime<Utc> {
+        &self.datetime
+    }
+
+    fn order(&self) -> Option<i64> {
+        self.order
+    }
+
+    fn set_order(&mut self, order: Option<i64>) {
+        self.order = order;
+    }
+
+    fn entry_type(&self) -> GooseberryEntryType {
+        GooseberryEntryType::Habit
+    }
+
+    fn title(&self) -> &str {
+        &self.habit
+    }
+
+    fn texts(&self) -> Vec<&str> {
+        vec![&self.habit, &self.notes]
+    }
+
+    fn body_mut(&mut self) -> &mut String {
+        &mut self.notes
+    }
+
+    fn to_markdown_string(&self) -> String {
+        let header = format!(
+            "{}\n{}\nHabit: {}\nStreak: {}\n{}{}\n",
+            utility::formatting::HEADER_MARK,
+            self.format_id_datetime_tags(),
+            self.habit,
+            self.streak,
+            format_extra_header(&self.extra),
+            utility::formatting::HEADER_MARK,
+        );
+        format!("{}{}", header, self.notes)
+    }
+
+    /// ID Habit
+    /// DateTime
+    /// Tags
+    /// Streak
+    fn to_tui_short(&self, format: DisplayFormat) -> Result<Vec<Text<'_>>, GooseberryError> {
+        let mut styled_text = utility::formatting::style_short(
             self.id,
-            &self.title,
+            &self.habit,
             None,
             &self.datetime,
             &self.tags,
-            terminal_width,
-            false,
-            false,
-            true,
-        ))
+            &[],
+            format
+                .with_body_features(BodyFeatures::from_markdown(&self.notes))
+                .with_title_style(CONFIG.title_styles.for_type(self.entry_type()))
+                .with_date_only(),
+        );
+        if !format.compact {
+            styled_text.push(utility::formatting::style_field(
+                "Streak",
+                self.streak.to_string(),
+                None,
+            ));
+        }
+        Ok(styled_text)
     }
 
-    /// Short
-    /// People
-    ///
-    /// Notes
-    fn to_tui_long(&self, terminal_width: u16) -> Result<Vec<Text>, Error> {
-        let mut styled_text = self.to_tui_short(terminal_width)?;
-        styled_text.push(utility::formatting::style_people(&self.people));
+    /// Adds notes to short
+    fn to_tui_long(&self, format: DisplayFormat) -> Result<Vec<Text<'_>>, GooseberryError> {
+        let mut styled_text = self.to_tui_short(format)?;
         styled_text.push(Text::Raw("\n".into()));
         styled_text.extend_from_slice(&utility::formatting::markdown_to_styled_texts(
-            &self.notes.trim(),
+            self.notes.trim(),
         ));
         styled_text.push(Text::Raw("\n".into()));
         styled_text.push(Text::Raw("\n".into()));
@@ -1007,5 +2262,363 @@ impl GooseberryEntryTrait for EventEntry {
     fn merge_with_entry(&mut self, old_entry: &Self) {
         self.id = old_entry.id;
         self.datetime = old_entry.datetime;
+        self.extra = old_entry.extra.clone();
+        self.order = old_entry.order;
+    }
+
+    /// The habit can't be empty
+    fn validate(&self) -> Result<(), Vec<String>> {
+        let mut problems = common_problems(self);
+        if self.habit.trim().is_empty() {
+            problems.push("The habit is empty".into());
+        }
+        problems_to_result(problems)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A file with the common header elements, `lines` are the type's own header lines
+    fn markdown(entry_type: &str, lines: &str, body: &str) -> String {
+        format!(
+            "---\nType: {}\nID: 1\nDateTime: 2020-01-01T10:00:00Z\nTags: work\n{}---\n{}",
+            entry_type, lines, body
+        )
+    }
+
+    #[test]
+    fn event_without_people() {
+        let g_entry = GooseberryEntry::from_markdown_string(&markdown(
+            "Event",
+            "Title: Seminar\nPeople: \n",
+            "",
+        ))
+        .unwrap();
+        match g_entry {
+            GooseberryEntry::Event(event) => assert!(event.people.is_empty()),
+            _ => panic!("not an Event"),
+        }
+    }
+
+    #[test]
+    fn event_needs_people_header() {
+        let error =
+            GooseberryEntry::from_markdown_string(&markdown("Event", "Title: Seminar\n", ""))
+                .unwrap_err();
+        match error {
+            GooseberryError::Sorry(Sorry::MissingHeaderElement { element }) => {
+                assert_eq!(element, "People")
+            }
+            error => panic!("unexpected error: {}", error),
+        }
+    }
+
+    #[test]
+    fn task_with_people_header() {
+        let markdown = markdown("Task", "Task: Buy milk\nDone: false\nPeople: Alice\n", "");
+        let g_entry = GooseberryEntry::from_markdown_string(&markdown).unwrap();
+        assert_eq!(g_entry.title(), "Buy milk");
+    }
+
+    fn tags(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn unquoted_tags_split_on_commas() {
+        assert_eq!(
+            parse_tags("work, machine learning,c"),
+            tags(&["work", "machine learning", "c"])
+        );
+    }
+
+    #[test]
+    fn empty_tags_are_dropped() {
+        assert_eq!(parse_tags("work, , urgent,"), tags(&["work", "urgent"]));
+        assert_eq!(parse_tags("work,,urgent"), tags(&["work", "urgent"]));
+        assert_eq!(parse_tags(" , ,"), tags(&[]));
+        assert_eq!(parse_tags(r#""", work"#), tags(&["work"]));
+    }
+
+    #[test]
+    fn duplicate_tags_are_dropped() {
+        assert_eq!(
+            parse_tags(r#"work, urgent,work , "urgent""#),
+            tags(&["work", "urgent"])
+        );
+    }
+
+    #[test]
+    fn quoted_tags_keep_commas() {
+        assert_eq!(parse_tags(r#""a,b", c"#), tags(&["a,b", "c"]));
+        assert_eq!(parse_tags(r#"c, "a,b""#), tags(&["c", "a,b"]));
+        assert_eq!(parse_tags(r#""a,b" ignored, c"#), tags(&["a,b", "c"]));
+    }
+
+    #[test]
+    fn unclosed_quote_is_part_of_the_tag() {
+        assert_eq!(parse_tags(r#""a, b"#), tags(&["\"a", "b"]));
+    }
+
+    #[test]
+    fn quoted_tags_round_trip() {
+        let parsed = tags(&["a,b", "c"]);
+        assert_eq!(parse_tags(&format_tags(&parsed)), parsed);
+    }
+
+    /// Reads an entry from a string and writes it back, the result should be the same string
+    fn assert_round_trip(markdown: &str) {
+        let g_entry = GooseberryEntry::from_markdown_string(markdown).unwrap();
+        assert_eq!(g_entry.to_markdown_string(), markdown);
+    }
+
+    #[test]
+    fn task_round_trip() {
+        assert_round_trip(&markdown("Task", "Task: Buy milk\nDone: false\n", ""));
+        assert_round_trip(
+            "---\nType: Task\nID: 3\nDateTime: 2020-01-01T10:00:00Z\nTags: work, \"a,b\"\nOrder: 2\n\
+             Task: Buy milk\nDone: true\nDue: 2020-01-02T23:59:59Z\nPriority: High\nProject: gooseberry\n\
+             ---\nSome *notes*\n",
+        );
+    }
+
+    #[test]
+    fn journal_round_trip() {
+        assert_round_trip(&markdown("Journal", "", "Slept in\n\nThen *more* sleep\n"));
+    }
+
+    #[test]
+    fn research_round_trip() {
+        assert_round_trip(&markdown(
+            "Research",
+            "Title: Gooseberries\n",
+            "# Notes\n- green\n",
+        ));
+    }
+
+    #[test]
+    fn event_round_trip() {
+        assert_round_trip(&markdown("Event", "Title: Seminar\nPeople: \n", ""));
+        assert_round_trip(&markdown(
+            "Event",
+            "Title: Seminar\nPeople: Alice (speaker), Bob\nLocation: Room 1\nStart: 2020-01-01T10:30:00Z\nEnd: 2020-01-01T11:30:00Z\n",
+            "Bring slides\n",
+        ));
+    }
+
+    #[test]
+    fn event_is_on_the_agenda_at_its_start() {
+        let written_only = GooseberryEntry::from_markdown_string(&markdown(
+            "Event",
+            "Title: Seminar\nPeople: \n",
+            "",
+        ))
+        .unwrap();
+        assert_eq!(
+            written_only.agenda_datetime(),
+            Some(*written_only.datetime())
+        );
+        assert!(written_only.is_past());
+        let upcoming = GooseberryEntry::from_markdown_string(&markdown(
+            "Event",
+            "Title: Seminar\nPeople: \nStart: 2999-01-01T10:00:00Z\n",
+            "",
+        ))
+        .unwrap();
+        assert_eq!(
+            upcoming.agenda_datetime(),
+            Some(parse_header_datetime("2999-01-01T10:00:00Z").unwrap())
+        );
+        assert!(!upcoming.is_past());
+    }
+
+    /// Color of an Event's title in the short display
+    fn title_color(event: &EventEntry) -> Option<tui::style::Color> {
+        event
+            .to_tui_short(DisplayFormat::new(80, 1))
+            .unwrap()
+            .into_iter()
+            .find_map(|text| match text {
+                Text::Styled(t, style) if t.contains(&event.title) => Some(style.fg),
+                _ => None,
+            })
+    }
+
+    #[test]
+    fn event_coming_up_is_colored_by_its_start() {
+        let mut event =
+            EventEntry::from_markdown_string(&markdown("Event", "Title: Seminar\nPeople: \n", ""))
+                .unwrap();
+        let default_color = title_color(&event);
+        assert_ne!(default_color, Some(CONFIG.soon_color));
+        event.start = Some(Utc::now() + chrono::Duration::hours(1));
+        assert_eq!(title_color(&event), Some(CONFIG.soon_color));
+        event.start = Some(Utc::now() + chrono::Duration::hours(CONFIG.soon_hours + 1));
+        assert_eq!(title_color(&event), default_color);
+    }
+
+    #[test]
+    fn start_with_only_a_time_or_a_day() {
+        let day = parse_header_datetime("2020-01-01T10:00:00Z").unwrap();
+        let starts = [
+            ("15:30", "2020-01-01T15:30:00Z"),
+            ("3:30 PM", "2020-01-01T15:30:00Z"),
+            ("2020-01-05", "2020-01-05T00:00:00Z"),
+            ("2020-01-05 09:15", "2020-01-05T09:15:00Z"),
+        ];
+        for (typed, start) in &starts {
+            assert_eq!(
+                format_header_datetime(&parse_start(typed, &day).unwrap()),
+                *start
+            );
+        }
+        assert!(parse_start("someday", &day).is_err());
+    }
+
+    #[test]
+    fn habit_round_trip() {
+        assert_round_trip(&markdown(
+            "Habit",
+            "Habit: Stretch\nStreak: 4\n",
+            "Every morning\n",
+        ));
+    }
+
+    #[test]
+    fn valid_entries() {
+        let markdowns = [
+            markdown("Task", "Task: Buy milk\nDone: false\n", ""),
+            markdown("Journal", "", "Slept in\n"),
+            markdown("Research", "Title: Gooseberries\n", ""),
+            markdown("Event", "Title: Seminar\nPeople: Alice\n", ""),
+            markdown("Habit", "Habit: Stretch\nStreak: 0\n", ""),
+        ];
+        for markdown in &markdowns {
+            assert_eq!(
+                GooseberryEntry::from_markdown_string(markdown)
+                    .unwrap()
+                    .validate(),
+                Ok(())
+            );
+        }
+    }
+
+    #[test]
+    fn task_validation() {
+        let mut task =
+            TaskEntry::from_markdown_string(&markdown("Task", "Task: Buy milk\nDone: false\n", ""))
+                .unwrap();
+        task.task = " ".into();
+        task.tags.push("a\"b".into());
+        assert_eq!(
+            task.validate(),
+            Err(vec![
+                "Tag \"a\\\"b\" can't have line breaks or double quotes".to_owned(),
+                "The task is empty".to_owned(),
+            ])
+        );
+    }
+
+    #[test]
+    fn journal_validation() {
+        let mut journal =
+            JournalEntry::from_markdown_string(&markdown("Journal", "", "Slept in\n")).unwrap();
+        journal.description = "\n".into();
+        assert_eq!(
+            journal.validate(),
+            Err(vec!["The description is empty".to_owned()])
+        );
+    }
+
+    #[test]
+    fn research_validation() {
+        let mut research =
+            ResearchEntry::from_markdown_string(&markdown("Research", "Title: Gooseberries\n", ""))
+                .unwrap();
+        research.title = String::new();
+        research.tags.push("a\nb".into());
+        assert_eq!(
+            research.validate(),
+            Err(vec![
+                "Tag \"a\\nb\" can't have line breaks or double quotes".to_owned(),
+                "The title is empty".to_owned(),
+            ])
+        );
+    }
+
+    #[test]
+    fn event_validation() {
+        let mut event = EventEntry::from_markdown_string(&markdown(
+            "Event",
+            "Title: Seminar\nPeople: Alice\nEnd: 2020-01-01T09:00:00Z\n",
+            "",
+        ))
+        .unwrap();
+        event.title = " ".into();
+        event.people[0].name = " ".into();
+        assert_eq!(
+            event.validate(),
+            Err(vec![
+                "The title is empty".to_owned(),
+                "It ends before it starts".to_owned(),
+                "Someone in People has no name".to_owned(),
+            ])
+        );
+        // ending after it's written isn't enough, it has to end after it starts
+        let event = EventEntry::from_markdown_string(&markdown(
+            "Event",
+            "Title: Seminar\nPeople: Alice\nStart: 2020-01-02T10:00:00Z\nEnd: 2020-01-01T12:00:00Z\n",
+            "",
+        ))
+        .unwrap();
+        assert_eq!(
+            event.validate(),
+            Err(vec!["It ends before it starts".to_owned()])
+        );
+    }
+
+    #[test]
+    fn habit_validation() {
+        let mut habit =
+            HabitEntry::from_markdown_string(&markdown("Habit", "Habit: Stretch\nStreak: 0\n", ""))
+                .unwrap();
+        habit.habit = String::new();
+        assert_eq!(habit.validate(), Err(vec!["The habit is empty".to_owned()]));
+    }
+
+    #[test]
+    fn header_datetime_in_both_formats() {
+        let new = "2020-01-01T10:00:00Z";
+        let datetime = parse_header_datetime(new).unwrap();
+        assert_eq!(format_header_datetime(&datetime), new);
+        // older files, read the same and written out as RFC 3339 from then on
+        let old = datetime.format(OLD_HEADER_DATETIME_FORMAT).to_string();
+        assert_eq!(parse_header_datetime(&old).unwrap(), datetime);
+        let old_markdown = markdown("Task", "Task: Buy milk\nDone: false\n", "").replace(new, &old);
+        let g_entry = GooseberryEntry::from_markdown_string(&old_markdown).unwrap();
+        assert_eq!(
+            g_entry.to_markdown_string(),
+            markdown("Task", "Task: Buy milk\nDone: false\n", "")
+        );
+    }
+
+    #[test]
+    fn save_replaces_the_file_and_leaves_no_temp_file() {
+        let folder = crate::utility::test_folder("atomic-write");
+        let markdown = markdown("Task", "Task: Buy milk\nDone: false\n", "");
+        let path = GooseberryEntryType::Task.file_path(&folder, 1);
+        fs::write(&path, "an older, longer version of the entry").unwrap();
+        let g_entry = GooseberryEntry::from_markdown_string(&markdown).unwrap();
+        g_entry.to_file(PathFile::new(&path).unwrap()).unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+        let files = fs::read_dir(&folder)
+            .unwrap()
+            .map(|file| file.unwrap().file_name())
+            .collect::<Vec<_>>();
+        folder.remove_all().unwrap();
+        assert_eq!(contents, markdown);
+        assert_eq!(files, vec![path.file_name().unwrap().to_owned()]);
     }
 }
@@ -1,11 +1,39 @@
+use std::{
+    io,
+    num::ParseIntError,
+    str::ParseBoolError,
+};
+
 use thiserror::Error;
 
 use crate::entry::GooseberryEntryType;
 
+/// What reading, writing and displaying entries (`entry`) can fail with, so callers can match on it
+/// Causes are kept as the `source`, the binary wraps all of this in `anyhow::Error`
+#[derive(Debug, Error)]
+pub enum GooseberryError {
+    #[error(transparent)]
+    Sorry(#[from] Sorry),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Path(#[from] path_abs::Error),
+    #[error(transparent)]
+    Glob(#[from] glob::GlobError),
+    #[error(transparent)]
+    GlobPattern(#[from] glob::PatternError),
+    #[error(transparent)]
+    ParseInt(#[from] ParseIntError),
+    #[error(transparent)]
+    ParseBool(#[from] ParseBoolError),
+    #[error(transparent)]
+    ParseDateTime(#[from] chrono::ParseError),
+}
+
 #[derive(Debug, Error)]
 pub enum Sorry {
     #[error(
-        "What's {entry_type:?}? I can only remember Tasks, Research, Events and Journal entries."
+        "What's {entry_type:?}? I can only remember Tasks, Research, Events, Habits and Journal entries."
     )]
     UnknownEntryType { entry_type: String },
     #[error("Entry {entry_type:?}_{entry_id:?} hasn't been written yet")]
@@ -13,8 +41,17 @@ pub enum Sorry {
         entry_type: GooseberryEntryType,
         entry_id: u64,
     },
+    #[error("There's already a {entry_type:?} with ID {entry_id}")]
+    DuplicateEntryID {
+        entry_type: GooseberryEntryType,
+        entry_id: u64,
+    },
     #[error("Every entry needs a header section (demarcated by ---) so I know what it's about")]
     MissingHeader,
+    #[error("The header section is never closed with another ---")]
+    UnclosedHeader,
+    #[error("Header lines look like 'Element: value', not {line:?}")]
+    MalformedHeaderLine { line: String },
     #[error("An entry of this type needs the '{element:?}' element in its header")]
     MissingHeaderElement { element: String },
     #[error("Expected {expected:?}, got {got:?}")]
@@ -22,6 +59,43 @@ pub enum Sorry {
         expected: GooseberryEntryType,
         got: GooseberryEntryType,
     },
+    #[error("Can't use {pattern:?} to name entry files: {reason}")]
+    InvalidFilePattern { pattern: String, reason: String },
+    #[error("Not saving {entry_type:?} {entry_id}, {path:?} already has entry {other_id}")]
+    PathCollision {
+        entry_type: GooseberryEntryType,
+        entry_id: u64,
+        other_id: u64,
+        path: String,
+    },
+    #[error("Found entry types I don't know about: {types:?}")]
+    UnexpectedEntryTypes { types: Vec<String> },
+    #[error("Can only handle {max} entry types, found {types:?}")]
+    TooManyEntryTypes { max: usize, types: Vec<String> },
+    #[error("Can't open the entries folder {path:?}: {reason}")]
+    MissingFolder { path: String, reason: String },
+    #[error("{entry_type:?} {entry_id} isn't right: {}", problems.join("; "))]
+    InvalidEntry {
+        entry_type: GooseberryEntryType,
+        entry_id: u64,
+        problems: Vec<String>,
+    },
+    #[error("Couldn't read the config file {path:?}: {reason}")]
+    InvalidConfig { path: String, reason: String },
+    #[error("There's no syntax theme called {theme:?}, pick one of: {}", available.join(", "))]
+    UnknownTheme { theme: String, available: Vec<String> },
+    #[error("Not archiving {entry_type:?} {entry_id}, {path:?} is already there")]
+    AlreadyArchived {
+        entry_type: GooseberryEntryType,
+        entry_id: u64,
+        path: String,
+    },
+    #[error("{editor:?} didn't finish cleanly ({status})")]
+    EditorFailed { editor: String, status: String },
+    #[error("Can't read {date:?} as a date, try e.g. 2026-10-31 or 2026-10-31 17:00")]
+    InvalidDate { date: String },
+    #[error("What's priority {priority:?}? Try high, medium, low or none")]
+    UnknownPriority { priority: String },
     #[error("Redo from start. {message:?}")]
     OutOfCheeseError { message: String },
 }
@@ -3,51 +3,277 @@
 #[macro_use]
 extern crate lazy_static;
 #[macro_use]
+extern crate log;
+#[macro_use]
 extern crate serde_derive;
 
 use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::Duration;
 
 use anyhow::Error;
 use crossterm::AlternateScreen;
-use path_abs::PathDir;
+use path_abs::PathFile;
 use tui::{backend::CrosstermBackend, Terminal};
 
 pub mod app;
 pub mod entry;
 pub mod errors;
+pub mod query;
 pub mod utility;
 
+/// Width used by `print` when the terminal size can't be found (e.g. when piping)
+const PRINT_WIDTH: u16 = 80;
+
+/// Options that take a value, so the value isn't mistaken for the folder
+const VALUE_OPTIONS: [&str; 2] = ["--tab", "--log"];
+
+/// The entries folder is the first argument that isn't an option, the current folder if there's none
+fn folder_arg(args: &[String]) -> Result<PathBuf, Error> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if VALUE_OPTIONS.contains(&arg.as_str()) {
+            args.next();
+        } else if !arg.starts_with("--") {
+            return Ok(PathBuf::from(arg));
+        }
+    }
+    Ok(std::env::current_dir()?)
+}
+
+/// `gooseberry print --tab <type> [--no-color] [folder]`
+/// Writes the entry list of one type to stdout instead of starting the TUI
+fn print_tab(args: &[String]) -> Result<(), Error> {
+    let tab_name = args
+        .iter()
+        .position(|arg| arg == "--tab")
+        .and_then(|i| args.get(i + 1))
+        .ok_or(errors::Sorry::OutOfCheeseError {
+            message: "print needs --tab <task|journal|research|event|habit>".into(),
+        })?;
+    let mut entry_type_name = tab_name.to_lowercase();
+    if let Some(first) = entry_type_name.get_mut(0..1) {
+        first.make_ascii_uppercase();
+    }
+    let entry_type = entry_type_name.parse::<entry::GooseberryEntryType>()?;
+    let color = !args.iter().any(|arg| arg == "--no-color");
+    let width = match crossterm::terminal().terminal_size() {
+        (0, _) => PRINT_WIDTH,
+        (width, _) => width,
+    };
+    let width = utility::config::CONFIG
+        .max_content_width
+        .map_or(width, |max_width| width.min(max_width));
+    let folder = app::entries_folder(&folder_arg(args)?, false)?;
+    let tab = app::GooseberryTab::from_folder(entry_type, &folder, true)?;
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    match writeln!(handle, "{}", tab.to_ansi(width, color)?) {
+        // the pager/head closed early, that's fine
+        Err(error) if error.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        written => Ok(written?),
+    }
+}
+
+/// `gooseberry lint [folder]`
+/// Reads every entry file without starting the TUI and prints each problem found as `<file>: <problem>`
+/// Returns true if there were any problems
+fn lint(args: &[String]) -> Result<bool, Error> {
+    use entry::GooseberryEntryTrait;
+
+    let folder = app::entries_folder(&folder_arg(args)?, false)?;
+    let mut issues = Vec::new();
+    for name in entry::entry_type_names(&folder)? {
+        if name.parse::<entry::GooseberryEntryType>().is_err() {
+            issues.push(format!(
+                "{}: files of unknown entry type {:?}",
+                folder.as_path().display(),
+                name
+            ));
+        }
+    }
+    for entry_type in &entry::GooseberryEntryType::ALL {
+        for file in glob::glob(&entry_type.glob_pattern(&folder))? {
+            let file = file?;
+            let file_name = file.display();
+            let file_id = match entry_type.id_from_file(&folder, &file) {
+                Some(id) => id,
+                None => continue,
+            };
+            let g_entry = match entry::GooseberryEntry::from_file(&PathFile::new(&file)?) {
+                Ok(g_entry) => g_entry,
+                Err(error) => {
+                    issues.push(format!("{}: can't be read: {}", file_name, error));
+                    continue;
+                }
+            };
+            if g_entry.entry_type() != *entry_type {
+                issues.push(format!(
+                    "{}: the header says it's a {}",
+                    file_name,
+                    g_entry.entry_type()
+                ));
+            }
+            if g_entry.id() != file_id {
+                issues.push(format!("{}: the header has ID {}", file_name, g_entry.id()));
+            }
+            if let Err(problems) = g_entry.validate() {
+                issues.extend(
+                    problems
+                        .into_iter()
+                        .map(|problem| format!("{}: {}", file_name, problem)),
+                );
+            }
+        }
+    }
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    for issue in &issues {
+        match writeln!(handle, "{}", issue) {
+            Err(error) if error.kind() == io::ErrorKind::BrokenPipe => break,
+            written => written?,
+        }
+    }
+    Ok(!issues.is_empty())
+}
+
+/// `gooseberry [--dry-run] [--log <file>] [folder]` opens the entries in `folder` (or the current folder)
+/// The folder is made if it isn't there yet (except in a dry run)
+/// `--log` writes what happens (keys, loads, saves, errors) to a file
 fn main() -> Result<(), Error> {
+    // a broken config file falls back to the defaults in CONFIG, so say what's wrong with it here
+    utility::config::GooseberryConfig::load()?;
+    utility::config::CONFIG.validate()?;
+
+    let args = std::env::args().collect::<Vec<_>>();
+    match args.get(1).map(String::as_str) {
+        Some("print") => return print_tab(&args[2..]),
+        Some("lint") => {
+            if lint(&args[2..])? {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        _ => (),
+    }
+
+    // --dry-run keeps all changes in memory
+    let dry_run = args.iter().any(|arg| arg == "--dry-run");
+
+    if let Some(i) = args.iter().position(|arg| arg == "--log") {
+        let log_file = args.get(i + 1).ok_or(errors::Sorry::OutOfCheeseError {
+            message: "--log needs a file to write to".into(),
+        })?;
+        utility::logging::init(&PathBuf::from(log_file))?;
+        info!("Starting with {:?}", args);
+    }
+
+    // App, loaded before the screen is taken over so errors are readable
+    let mut gooseberry =
+        app::GooseberryTabs::from_folder_or_create(&folder_arg(&args[1..])?, dry_run)?;
+
     // Terminal initialization
     let screen = AlternateScreen::to_alternate(true)?;
     let backend = CrosstermBackend::with_alternate_screen(screen)?;
     let mut terminal = Terminal::new(backend)?;
     terminal.hide_cursor()?;
 
-    // Keep track of keyboard events
+    // Keep track of keyboard events, and entry files changed by something else
     let events = utility::interactive::Events::default();
-
-    // App
-    let mut gooseberry = app::GooseberryTabs::from_folder(&PathDir::new("test_entries")?)?;
+    if utility::config::CONFIG.watch_interval_ms > 0 {
+        events.watch(
+            gooseberry.watch_patterns(),
+            Duration::from_millis(utility::config::CONFIG.watch_interval_ms),
+        );
+    }
     terminal.clear()?;
 
+    // Window title, put back as it was on quit
+    let mut window_title = String::new();
+    if utility::config::CONFIG.terminal_title {
+        utility::interactive::push_terminal_title()?;
+    }
+
+    utility::interactive::set_cursor_shape(utility::config::CONFIG.cursor_style)?;
+
     // Main rendering loop
     loop {
         terminal.draw(|mut f| gooseberry.render(&mut f))?;
 
+        // the terminal's cursor goes where typing goes, or the input boxes draw a fake one
+        let size = terminal.get_frame().size();
+        match gooseberry.cursor_position(size) {
+            Some((x, y)) if !utility::interactive::fake_cursor() => {
+                if terminal
+                    .set_cursor(x, y)
+                    .and_then(|_| terminal.show_cursor())
+                    .is_err()
+                {
+                    utility::interactive::use_fake_cursor();
+                    terminal.hide_cursor().ok();
+                }
+            }
+            _ => terminal.hide_cursor()?,
+        }
+
+        if utility::config::CONFIG.terminal_title && gooseberry.window_title() != window_title {
+            window_title = gooseberry.window_title();
+            utility::interactive::set_terminal_title(&window_title)?;
+        }
+
         // flush immediately so that you see each character as you type
         if gooseberry.is_writing() {
             io::stdout().flush().ok();
         }
 
         // Handle keyboard input
-        if let Ok(utility::interactive::Event::Input(key)) = events.next() {
-            let size = terminal.get_frame().size();
-            let should_break = gooseberry.keypress(size, key)?;
-            if should_break {
-                break;
+        match events.next() {
+            Ok(utility::interactive::Event::Input(key)) => {
+                let size = terminal.get_frame().size();
+                debug!("Key {:?}", key);
+                let should_break = gooseberry.keypress(size, key).map_err(|error| {
+                    error!("Stopped after {}", error);
+                    error
+                })?;
+                if should_break {
+                    break;
+                }
+            }
+            Ok(utility::interactive::Event::FileChanged(path)) => {
+                debug!("{} changed", path.display());
+                gooseberry.file_changed(&path);
             }
+            _ => (),
+        }
+
+        // `E <id>` hands the terminal to $EDITOR until it closes
+        if let Some(file) = gooseberry.editor_file() {
+            utility::interactive::reset_cursor_shape()?;
+            utility::interactive::suspend_screen()?;
+            info!("Opening {} in an editor", file.display());
+            let edited = utility::interactive::open_in_editor(&file);
+            utility::interactive::resume_screen()?;
+            utility::interactive::set_cursor_shape(utility::config::CONFIG.cursor_style)?;
+            terminal.clear()?;
+            gooseberry.finish_editor(edited);
         }
     }
+
+    if utility::config::CONFIG.terminal_title {
+        utility::interactive::pop_terminal_title()?;
+    }
+    utility::interactive::reset_cursor_shape()?;
+
+    gooseberry.session().store()?;
+    info!("Quitting");
+
+    // Remember the syntax theme, date display, and tab order picked at runtime
+    let mut config = utility::config::CONFIG.clone();
+    config.syntax_theme = utility::formatting::current_theme();
+    config.relative_dates = utility::formatting::relative_dates();
+    config.tab_order = gooseberry.tab_order();
+    if config != *utility::config::CONFIG {
+        config.store()?;
+    }
     Ok(())
 }
@@ -0,0 +1,357 @@
+use std::{cmp::Ordering, collections::HashMap, fmt, fs, time::SystemTime};
+
+use chrono::{DateTime, Utc};
+use path_abs::PathDir;
+
+use crate::entry::{GooseberryEntry, GooseberryEntryTrait, GooseberryEntryType};
+use crate::utility::config::CONFIG;
+
+/// How to order entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortBy {
+    /// as the TUI lists them by default, see `compare_listed`
+    Created,
+    /// when the entry's file was last changed, entries without a file come first
+    /// (ties are listed as in the TUI)
+    Modified,
+    /// alphabetically by title, ignoring case, the same as sorting a tab by title
+    Title,
+    /// high priority Tasks first, the same as sorting a tab by priority
+    Priority,
+}
+
+/// What the entries in a tab are sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortKey {
+    /// as listed by default, see `compare_listed` (entries moved by hand keep their place)
+    #[default]
+    Date,
+    Id,
+    /// alphabetically, ignoring case
+    Title,
+    /// open Tasks first
+    Done,
+    /// high priority Tasks first
+    Priority,
+}
+
+impl SortKey {
+    /// The next key to sort a tab by, Done and Priority only for Tasks
+    pub fn next(self, entry_type: GooseberryEntryType) -> Self {
+        match (self, entry_type) {
+            (SortKey::Date, _) => SortKey::Id,
+            (SortKey::Id, _) => SortKey::Title,
+            (SortKey::Title, GooseberryEntryType::Task) => SortKey::Done,
+            (SortKey::Done, GooseberryEntryType::Task) => SortKey::Priority,
+            _ => SortKey::Date,
+        }
+    }
+
+    /// Ascending comparison, ties are broken by `compare_listed`
+    pub fn compare(self, a: &GooseberryEntry, b: &GooseberryEntry) -> Ordering {
+        let ordering = match self {
+            SortKey::Date => Ordering::Equal,
+            SortKey::Id => a.id().cmp(&b.id()),
+            SortKey::Title => compare_title(a, b),
+            SortKey::Done => compare_done(a, b),
+            SortKey::Priority => a.priority().cmp(&b.priority()),
+        };
+        ordering.then_with(|| compare_listed(a, b))
+    }
+}
+
+/// How a tab's entries are sorted: by what, and which way round
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ListOrder {
+    pub key: SortKey,
+    pub descending: bool,
+}
+
+impl ListOrder {
+    pub fn compare(self, a: &GooseberryEntry, b: &GooseberryEntry) -> Ordering {
+        let ordering = self.key.compare(a, b);
+        if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+
+    /// The default order, the only one entries can be moved by hand in
+    pub fn is_listed(self) -> bool {
+        self == ListOrder::default()
+    }
+}
+
+impl fmt::Display for ListOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let direction = if self.descending {
+            "descending"
+        } else {
+            "ascending"
+        };
+        write!(f, "{:?}, {}", self.key, direction)
+    }
+}
+
+/// Conditions for picking out entries, an entry has to match all the ones that are set
+#[derive(Debug, Clone, Default)]
+pub struct EntryFilter {
+    /// only entries of this type
+    pub entry_type: Option<GooseberryEntryType>,
+    /// only entries with this tag, or a tag under it (`work` matches `work/projectx`)
+    pub tag: Option<String>,
+    /// only entries containing this text (case-insensitive)
+    pub text: Option<String>,
+    /// only entries written at or after this time
+    pub from: Option<DateTime<Utc>>,
+    /// only entries written before this time
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl EntryFilter {
+    /// True if no condition is set, so everything matches
+    pub fn is_empty(&self) -> bool {
+        self.entry_type.is_none()
+            && self.tag.is_none()
+            && self.text.is_none()
+            && self.from.is_none()
+            && self.to.is_none()
+    }
+
+    /// Checks if an entry satisfies every condition of the filter
+    pub fn matches(&self, g_entry: &GooseberryEntry) -> bool {
+        if let Some(entry_type) = self.entry_type {
+            if g_entry.entry_type() != entry_type {
+                return false;
+            }
+        }
+        if let Some(tag) = &self.tag {
+            if !g_entry.tags().iter().any(|t| tag_matches(t, tag)) {
+                return false;
+            }
+        }
+        if let Some(text) = &self.text {
+            let text = text.to_lowercase();
+            if !g_entry
+                .texts()
+                .iter()
+                .any(|t| t.to_lowercase().contains(&text))
+            {
+                return false;
+            }
+        }
+        if let Some(from) = self.from {
+            if *g_entry.datetime() < from {
+                return false;
+            }
+        }
+        if let Some(to) = self.to {
+            if *g_entry.datetime() >= to {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Tags are hierarchical with `/`, so a tag matches itself and any parent
+/// e.g. `work/projectx` matches `work` and `work/projectx` but not `work/project` or `projectx`
+pub fn tag_matches(tag: &str, filter_tag: &str) -> bool {
+    tag == filter_tag
+        || (tag.starts_with(filter_tag) && tag[filter_tag.len()..].starts_with('/'))
+}
+
+/// Each tag used by the entries with how many entries have it, most used first (then alphabetically)
+pub fn tag_counts<'a>(entries: impl Iterator<Item = &'a GooseberryEntry>) -> Vec<(String, usize)> {
+    let mut counts = HashMap::new();
+    for g_entry in entries {
+        for tag in g_entry.tags() {
+            *counts.entry(tag.as_str()).or_insert(0) += 1;
+        }
+    }
+    let mut counts = counts
+        .into_iter()
+        .map(|(tag, count)| (tag.to_owned(), count))
+        .collect::<Vec<_>>();
+    counts.sort_by(|(tag_a, count_a), (tag_b, count_b)| {
+        count_b.cmp(count_a).then_with(|| tag_a.cmp(tag_b))
+    });
+    counts
+}
+
+/// Orders two entries by when they were written, then by ID
+pub fn compare_created(a: &GooseberryEntry, b: &GooseberryEntry) -> Ordering {
+    a.datetime()
+        .cmp(b.datetime())
+        .then_with(|| a.id().cmp(&b.id()))
+}
+
+/// Orders two entries alphabetically by title, ignoring case
+pub fn compare_title(a: &GooseberryEntry, b: &GooseberryEntry) -> Ordering {
+    a.title().to_lowercase().cmp(&b.title().to_lowercase())
+}
+
+/// Puts open Tasks before done ones, everything else is equal
+pub fn compare_done(a: &GooseberryEntry, b: &GooseberryEntry) -> Ordering {
+    a.is_done().cmp(&b.is_done())
+}
+
+/// Puts entries moved by hand first, in their `order`, everything else is equal
+pub fn compare_order(a: &GooseberryEntry, b: &GooseberryEntry) -> Ordering {
+    match (a.order(), b.order()) {
+        (Some(order_a), Some(order_b)) => order_a.cmp(&order_b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// How entries are listed in the TUI: open Tasks first if `CONFIG.done_tasks_last` is set,
+/// then by `order`, then by when they were written
+pub fn compare_listed(a: &GooseberryEntry, b: &GooseberryEntry) -> Ordering {
+    let done = if CONFIG.done_tasks_last {
+        compare_done(a, b)
+    } else {
+        Ordering::Equal
+    };
+    done.then_with(|| compare_order(a, b))
+        .then_with(|| compare_created(a, b))
+}
+
+/// When an entry's file in `folder` was last modified, None if it can't be read
+fn modified_time(g_entry: &GooseberryEntry, folder: Option<&PathDir>) -> Option<SystemTime> {
+    fs::metadata(g_entry.entry_type().file_path(folder?, g_entry.id()))
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Sorts entries in place with the same comparisons as the TUI, ties are broken by `compare_listed`
+/// `folder` gives where the files of each entry type are, only used for `SortBy::Modified`
+pub fn sort_entries<'a>(
+    entries: &mut Vec<&GooseberryEntry>,
+    sort_by: SortBy,
+    folder: impl Fn(GooseberryEntryType) -> Option<&'a PathDir>,
+) {
+    match sort_by {
+        SortBy::Created => entries.sort_by(|a, b| SortKey::Date.compare(a, b)),
+        SortBy::Modified => {
+            let mut with_times = entries
+                .drain(..)
+                .map(|e| (modified_time(e, folder(e.entry_type())), e))
+                .collect::<Vec<_>>();
+            with_times.sort_by(|(time_a, a), (time_b, b)| {
+                time_a.cmp(time_b).then_with(|| compare_listed(a, b))
+            });
+            entries.extend(with_times.into_iter().map(|(_, e)| e));
+        }
+        SortBy::Title => entries.sort_by(|a, b| SortKey::Title.compare(a, b)),
+        SortBy::Priority => entries.sort_by(|a, b| SortKey::Priority.compare(a, b)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use path_abs::PathFile;
+
+    use super::*;
+    use crate::utility::test_folder;
+
+    fn task(id: u64, task: &str, day: u32, priority: &str) -> GooseberryEntry {
+        GooseberryEntry::from_markdown_string(&format!(
+            "---\nType: Task\nID: {}\nDateTime: 2020-01-{:02}T10:00:00Z\nTags: \nTask: {}\nDone: false\nPriority: {}\n---\n",
+            id, day, task, priority
+        ))
+        .unwrap()
+    }
+
+    fn sorted_ids(
+        entries: &[GooseberryEntry],
+        sort_by: SortBy,
+        folder: Option<&PathDir>,
+    ) -> Vec<u64> {
+        let mut entries = entries.iter().collect::<Vec<_>>();
+        sort_entries(&mut entries, sort_by, |_| folder);
+        entries.iter().map(|e| e.id()).collect()
+    }
+
+    #[test]
+    fn sorts_by_created() {
+        let entries = vec![
+            task(1, "b", 3, "low"),
+            task(2, "a", 1, "high"),
+            task(3, "c", 2, ""),
+        ];
+        assert_eq!(sorted_ids(&entries, SortBy::Created, None), vec![2, 3, 1]);
+    }
+
+    #[test]
+    fn sorts_by_title_ignoring_case() {
+        let entries = vec![
+            task(1, "b", 1, ""),
+            task(2, "C", 2, ""),
+            task(3, "A", 3, ""),
+        ];
+        assert_eq!(sorted_ids(&entries, SortBy::Title, None), vec![3, 1, 2]);
+    }
+
+    #[test]
+    fn sorts_by_priority() {
+        let entries = vec![
+            task(1, "a", 1, ""),
+            task(2, "b", 2, "low"),
+            task(3, "c", 3, "high"),
+            task(4, "d", 4, "medium"),
+        ];
+        assert_eq!(
+            sorted_ids(&entries, SortBy::Priority, None),
+            vec![3, 4, 2, 1]
+        );
+    }
+
+    #[test]
+    fn sorts_by_modified() {
+        let folder = test_folder("sort-modified");
+        let entries = vec![
+            task(1, "a", 1, ""),
+            task(2, "b", 2, ""),
+            task(3, "c", 3, ""),
+        ];
+        // entry 2 has no file so comes first, then 3 and 1 in the order they were written
+        for id in &[3, 1] {
+            let g_entry = &entries[*id as usize - 1];
+            let path = PathFile::create(g_entry.entry_type().file_path(&folder, *id)).unwrap();
+            g_entry.to_file(path).unwrap();
+            thread::sleep(Duration::from_millis(20));
+        }
+        let ids = sorted_ids(&entries, SortBy::Modified, Some(&folder));
+        folder.remove_all().unwrap();
+        assert_eq!(ids, vec![2, 3, 1]);
+    }
+
+    /// Same as `task`, moved by hand to `order`
+    fn ordered_task(id: u64, task_name: &str, day: u32, order: i64) -> GooseberryEntry {
+        let mut markdown = task(id, task_name, day, "").to_markdown_string();
+        markdown = markdown.replace("\nTask: ", &format!("\nOrder: {}\nTask: ", order));
+        GooseberryEntry::from_markdown_string(&markdown).unwrap()
+    }
+
+    #[test]
+    fn sorts_like_the_tui_with_order() {
+        let entries = vec![
+            task(1, "a", 1, ""),
+            ordered_task(2, "b", 2, 1),
+            ordered_task(3, "a", 3, 0),
+            task(4, "c", 4, ""),
+        ];
+        let mut listed = entries.iter().collect::<Vec<_>>();
+        listed.sort_by(|a, b| ListOrder::default().compare(a, b));
+        let listed = listed.iter().map(|e| e.id()).collect::<Vec<_>>();
+        assert_eq!(listed, vec![3, 2, 1, 4]);
+        assert_eq!(sorted_ids(&entries, SortBy::Created, None), listed);
+        // same titles are listed as in the TUI too
+        assert_eq!(sorted_ids(&entries, SortBy::Title, None), vec![3, 1, 2, 4]);
+    }
+}
@@ -1,7 +1,24 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Error;
+use directories::ProjectDirs;
 use tui::style::Color;
 
+use crate::entry::{
+    GooseberryEntryType, JournalGrouping, Priority, ID_PLACEHOLDER, TYPE_PLACEHOLDER,
+};
+use crate::errors::Sorry;
+use crate::utility::formatting::{
+    theme_names, CursorStyle, DateOrder, IdPadding, MetadataLine, TitleStyle, DEFAULT_METADATA_ORDER,
+};
+use crate::utility::interactive::{CopyTarget, UnknownKeyAction};
+
+/// Name of the config file (gooseberry-tm.toml) and its folder
+pub(crate) const CONFIG_NAME: &str = "gooseberry-tm";
+
 lazy_static! {
-    pub static ref CONFIG: GooseberryConfig = GooseberryConfig::default();
+    /// Read from the config file once, defaults if it can't be read (`main` reports why first)
+    pub static ref CONFIG: GooseberryConfig = GooseberryConfig::load().unwrap_or_default();
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -28,32 +45,388 @@ enum GooseberryColor {
     Indexed(u8),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Title styling for each entry type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TitleStyles {
+    pub task: TitleStyle,
+    pub journal: TitleStyle,
+    pub research: TitleStyle,
+    pub event: TitleStyle,
+    pub habit: TitleStyle,
+}
+
+impl TitleStyles {
+    pub fn for_type(&self, entry_type: GooseberryEntryType) -> TitleStyle {
+        match entry_type {
+            GooseberryEntryType::Task => self.task,
+            GooseberryEntryType::Journal => self.journal,
+            GooseberryEntryType::Research => self.research,
+            GooseberryEntryType::Event => self.event,
+            GooseberryEntryType::Habit => self.habit,
+        }
+    }
+}
+
+impl Default for TitleStyles {
+    /// Bold italics, except Journal descriptions which are just italics
+    fn default() -> Self {
+        let bold_italic = TitleStyle {
+            bold: true,
+            italic: true,
+            underlined: false,
+        };
+        Self {
+            task: bold_italic,
+            journal: TitleStyle {
+                bold: false,
+                ..bold_italic
+            },
+            research: bold_italic,
+            event: bold_italic,
+            habit: bold_italic,
+        }
+    }
+}
+
+/// Order of the metadata lines for each entry type, lines that are left out aren't shown
+/// Only changes the display, files are always written the same way
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetadataOrders {
+    pub task: Vec<MetadataLine>,
+    pub journal: Vec<MetadataLine>,
+    pub research: Vec<MetadataLine>,
+    pub event: Vec<MetadataLine>,
+    pub habit: Vec<MetadataLine>,
+}
+
+impl MetadataOrders {
+    pub fn for_type(&self, entry_type: GooseberryEntryType) -> &[MetadataLine] {
+        match entry_type {
+            GooseberryEntryType::Task => &self.task,
+            GooseberryEntryType::Journal => &self.journal,
+            GooseberryEntryType::Research => &self.research,
+            GooseberryEntryType::Event => &self.event,
+            GooseberryEntryType::Habit => &self.habit,
+        }
+    }
+}
+
+impl Default for MetadataOrders {
+    fn default() -> Self {
+        Self {
+            task: DEFAULT_METADATA_ORDER.to_vec(),
+            journal: DEFAULT_METADATA_ORDER.to_vec(),
+            research: DEFAULT_METADATA_ORDER.to_vec(),
+            event: DEFAULT_METADATA_ORDER.to_vec(),
+            habit: DEFAULT_METADATA_ORDER.to_vec(),
+        }
+    }
+}
+
+/// Shown in the list for entries with attachments, links or checklists in their description/notes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BodyGlyphs {
+    pub attachment: String,
+    pub link: String,
+    pub subtask: String,
+}
+
+impl Default for BodyGlyphs {
+    fn default() -> Self {
+        Self {
+            attachment: "📎".into(),
+            link: "🔗".into(),
+            subtask: "☑".into(),
+        }
+    }
+}
+
+/// Title colors of Tasks by priority (Tasks without one keep the default color)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PriorityColors {
+    #[serde(with = "GooseberryColor")]
+    pub high: Color,
+    #[serde(with = "GooseberryColor")]
+    pub medium: Color,
+    #[serde(with = "GooseberryColor")]
+    pub low: Color,
+}
+
+impl PriorityColors {
+    pub fn for_priority(&self, priority: Priority) -> Option<Color> {
+        match priority {
+            Priority::High => Some(self.high),
+            Priority::Medium => Some(self.medium),
+            Priority::Low => Some(self.low),
+            Priority::None => None,
+        }
+    }
+}
+
+impl Default for PriorityColors {
+    fn default() -> Self {
+        Self {
+            high: Color::Magenta,
+            medium: Color::Cyan,
+            low: Color::DarkGray,
+        }
+    }
+}
+
+/// Settings missing from the config file get their default value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct GooseberryConfig {
     pub syntax_theme: String,
+    /// themes to cycle through at runtime
+    pub syntax_themes: Vec<String>,
     #[serde(with = "GooseberryColor")]
     pub primary_metadata_color: Color,
     #[serde(with = "GooseberryColor")]
     pub secondary_metadata_color: Color,
+    /// drawn as the cursor in the input boxes with `CursorStyle::Bar`
     pub cursor_char: char,
+    /// how the cursor in the input boxes looks
+    pub cursor_style: CursorStyle,
     #[serde(with = "GooseberryColor")]
     pub cursor_color: Color,
     #[serde(with = "GooseberryColor")]
     pub tab_inactive_color: Color,
     #[serde(with = "GooseberryColor")]
     pub tab_active_color: Color,
+    /// how ids are padded in the list display
+    pub id_padding: IdPadding,
+    /// which tabs to show, from left to right
+    pub tab_order: Vec<GooseberryEntryType>,
+    /// shown (dimmed) before entries with changes that couldn't be saved
+    pub unsaved_marker: char,
+    /// true => Journal descriptions are rendered as markdown
+    pub markdown_journal: bool,
+    /// which day Journal entries are listed under
+    pub journal_grouping: JournalGrouping,
+    /// where entries are saved, relative to the entries folder
+    /// `{type}` is replaced by the entry type and `{id}` by the entry ID, each has to appear once
+    /// e.g. "{type}_{id}.md" (default), "{type}_{id}.markdown", "{type}/{id}.md"
+    pub entry_file_pattern: String,
+    /// lines scrolled per Up/Down press
+    pub scroll_step: u16,
+    /// lines scrolled per PageUp/PageDown press
+    pub coarse_scroll_step: u16,
+    /// how often (in milliseconds) entry files are checked for changes made outside gooseberry,
+    /// by polling their modification times (no OS notifications, so it works the same everywhere)
+    /// 0 => never, changes show up after a restart (or `l <id>`)
+    pub watch_interval_ms: u64,
+    /// true => a symlinked entries folder is resolved to the real folder before loading,
+    /// so globbing and writes all go to the same place
+    pub resolve_symlinks: bool,
+    /// drawn as a line between entries in the list (not in compact mode), None => no line
+    pub entry_divider: Option<char>,
+    /// entry text wraps at this many columns even on wider terminals, None => as wide as the terminal
+    pub max_content_width: Option<u16>,
+    /// true => the narrowed entry list is centered, otherwise it's on the left
+    pub center_content: bool,
+    /// order of day, month, and year in displayed dates
+    pub date_order: DateOrder,
+    /// true => datetimes are shown relative to now (e.g. "2 days ago"), toggled with `r`
+    pub relative_dates: bool,
+    /// true => the terminal window title shows the active tab and its entry count
+    pub terminal_title: bool,
+    /// title color of things past their deadline
+    #[serde(with = "GooseberryColor")]
+    pub overdue_color: Color,
+    /// title color of things coming up within `soon_hours`
+    #[serde(with = "GooseberryColor")]
+    pub soon_color: Color,
+    /// how many hours ahead counts as soon
+    pub soon_hours: i64,
+    /// what happens on keys that don't do anything
+    pub unknown_key: UnknownKeyAction,
+    /// where quick captures (`i`) go, whichever tab is active
+    pub inbox_type: GooseberryEntryType,
+    /// type used when it can't be told otherwise: entry files without a `Type:` line, and `+ [Enter]`
+    /// None => those files can't be read and `+` needs a type key
+    pub default_entry_type: Option<GooseberryEntryType>,
+    /// true => tags are shown in alphabetical order (ignoring case), files keep the order they were typed in
+    pub sort_tags: bool,
+    /// true => tags that only differ in case are shown once (the first one), files keep all of them
+    pub dedup_tags_display: bool,
+    /// whether `y <id>` copies the entry's ID or its file path
+    pub copy_target: CopyTarget,
+    /// most entry types (and so tabs) a folder can have
+    pub max_entry_types: usize,
+    /// true => done Tasks are always listed after open ones
+    pub done_tasks_last: bool,
+    /// how many changes the activity log (`L`) remembers
+    pub activity_log_length: usize,
+    /// true => a filter that matches nothing is dropped (with a message) instead of showing an empty list
+    pub clear_empty_filter: bool,
+    /// where the syntax highlighting themes and syntaxes are cached so they load faster, None => no cache
+    pub syntax_cache_folder: Option<String>,
+    // settings that are tables in the file go last, toml can't have plain values after a table
+    /// how titles are emphasized in each tab
+    pub title_styles: TitleStyles,
+    /// order of the title, date/tags, and people lines in each tab
+    pub metadata_orders: MetadataOrders,
+    /// glyphs for attachments, links and subtasks
+    pub body_glyphs: BodyGlyphs,
+    /// Task title colors for each priority, overdue/soon colors win over these
+    pub priority_colors: PriorityColors,
 }
 
 impl Default for GooseberryConfig {
     fn default() -> Self {
         Self {
             syntax_theme: "base16-ocean.dark".into(),
+            syntax_themes: vec![
+                "base16-ocean.dark".into(),
+                "base16-eighties.dark".into(),
+                "base16-mocha.dark".into(),
+                "Solarized (dark)".into(),
+                "base16-ocean.light".into(),
+                "InspiredGitHub".into(),
+                "Solarized (light)".into(),
+            ],
             primary_metadata_color: Color::Blue,
             secondary_metadata_color: Color::Green,
             cursor_char: '|',
+            cursor_style: CursorStyle::Bar,
             cursor_color: Color::Gray,
             tab_inactive_color: Color::LightGreen,
             tab_active_color: Color::Blue,
+            id_padding: IdPadding::None,
+            tab_order: vec![
+                GooseberryEntryType::Task,
+                GooseberryEntryType::Journal,
+                GooseberryEntryType::Research,
+                GooseberryEntryType::Event,
+                GooseberryEntryType::Habit,
+            ],
+            unsaved_marker: '*',
+            markdown_journal: false,
+            journal_grouping: JournalGrouping::Created,
+            entry_file_pattern: "{type}_{id}.md".into(),
+            scroll_step: 1,
+            coarse_scroll_step: 10,
+            watch_interval_ms: 1000,
+            resolve_symlinks: true,
+            entry_divider: None,
+            max_content_width: None,
+            center_content: false,
+            date_order: DateOrder::Mdy,
+            relative_dates: false,
+            terminal_title: false,
+            overdue_color: Color::Red,
+            soon_color: Color::Yellow,
+            soon_hours: 24,
+            unknown_key: UnknownKeyAction::Ignore,
+            title_styles: TitleStyles::default(),
+            inbox_type: GooseberryEntryType::Journal,
+            default_entry_type: None,
+            metadata_orders: MetadataOrders::default(),
+            sort_tags: false,
+            dedup_tags_display: false,
+            copy_target: CopyTarget::Id,
+            max_entry_types: GooseberryEntryType::ALL.len(),
+            done_tasks_last: false,
+            activity_log_length: 100,
+            clear_empty_filter: false,
+            syntax_cache_folder: None,
+            body_glyphs: BodyGlyphs::default(),
+            priority_colors: PriorityColors::default(),
         }
     }
 }
+
+impl GooseberryConfig {
+    /// Checks for settings that would break loading or saving entries, or displaying them
+    pub fn validate(&self) -> Result<(), Error> {
+        let pattern = &self.entry_file_pattern;
+        let invalid_pattern = |reason: &str| Sorry::InvalidFilePattern {
+            pattern: pattern.to_owned(),
+            reason: reason.into(),
+        };
+        if pattern.matches(ID_PLACEHOLDER).count() != 1 {
+            return Err(invalid_pattern("it needs exactly one {id}").into());
+        }
+        if pattern.matches(TYPE_PLACEHOLDER).count() != 1 {
+            return Err(invalid_pattern("it needs exactly one {type}").into());
+        }
+        if pattern.contains(['*', '?', '[', ']']) {
+            return Err(invalid_pattern("it can't have glob characters (*?[])").into());
+        }
+        if pattern.contains(&format!("{}{}", ID_PLACEHOLDER, TYPE_PLACEHOLDER))
+            || pattern.contains(&format!("{}{}", TYPE_PLACEHOLDER, ID_PLACEHOLDER))
+        {
+            return Err(invalid_pattern("{id} and {type} need something in between").into());
+        }
+        let themes = theme_names();
+        if !themes.contains(&self.syntax_theme) {
+            return Err(Sorry::UnknownTheme {
+                theme: self.syntax_theme.clone(),
+                available: themes,
+            }
+            .into());
+        }
+        let mut tab_types: Vec<GooseberryEntryType> = Vec::new();
+        for entry_type in &self.tab_order {
+            if !tab_types.contains(entry_type) {
+                tab_types.push(*entry_type);
+            }
+        }
+        if tab_types.len() > self.max_entry_types {
+            return Err(Sorry::TooManyEntryTypes {
+                max: self.max_entry_types,
+                types: tab_types.iter().map(|t| t.to_string()).collect(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Where the config file lives, e.g. ~/.config/gooseberry-tm/gooseberry-tm.toml on Linux
+    pub fn path() -> PathBuf {
+        ProjectDirs::from("rs", CONFIG_NAME, CONFIG_NAME)
+            .config_dir()
+            .join(format!("{}.toml", CONFIG_NAME))
+    }
+
+    /// Reads gooseberry-tm.toml from the user's config folder
+    /// If it's not there yet, it's written with the defaults
+    pub fn load() -> Result<Self, Error> {
+        let path = Self::path();
+        if !path.exists() {
+            let config = Self::default();
+            config.store()?;
+            return Ok(config);
+        }
+        toml::from_str(&fs::read_to_string(&path)?).map_err(|error| {
+            Sorry::InvalidConfig {
+                path: path.display().to_string(),
+                reason: error.to_string(),
+            }
+            .into()
+        })
+    }
+
+    /// Writes the config to gooseberry-tm.toml in the user's config folder
+    pub fn store(&self) -> Result<(), Error> {
+        confy::store(CONFIG_NAME, self)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn date_order_from_toml() {
+        let config: GooseberryConfig = toml::from_str("date_order = \"Dmy\"\n").unwrap();
+        assert_eq!(config.date_order, DateOrder::Dmy);
+        assert_eq!(GooseberryConfig::default().date_order, DateOrder::Mdy);
+    }
+}
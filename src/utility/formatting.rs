@@ -1,5 +1,9 @@
-use chrono::{Date, DateTime, NaiveTime, Utc};
+use std::{collections::HashSet, fs, path::Path, sync::RwLock};
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+use serde::{de::DeserializeOwned, Serialize};
 use syntect::{
+    dumps::{dump_to_file, from_dump_file},
     easy::HighlightLines,
     highlighting::{FontStyle, Style as SyntectStyle, Theme, ThemeSet},
     parsing::{SyntaxReference, SyntaxSet},
@@ -9,7 +13,7 @@ use tui::{
     style::{Color as TuiColor, Modifier, Style as TuiStyle},
     widgets::Text,
 };
-use unicode_width::UnicodeWidthStr;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 use crate::utility::config::CONFIG;
 
@@ -43,28 +47,118 @@ impl TaskState {
         }
     }
 
+    /// Symbol and the space after it, as it's shown before the title
+    fn marker(self) -> String {
+        format!("{} ", self.symbol())
+    }
+
+    /// How many columns the marker takes up (symbols can be double-width)
+    fn marker_width(self) -> usize {
+        self.marker().width()
+    }
+
     /// Put the color onto the symbol
     fn styled_symbol<'a>(self) -> Text<'a> {
-        Text::Styled(
-            format!("{} ", self.symbol()).into(),
-            TuiStyle::default().fg(self.color()),
-        )
+        Text::Styled(self.marker().into(), TuiStyle::default().fg(self.color()))
     }
 }
 
 lazy_static! {
-    /// Load theme sets
-    /// TODO: Save to file maybe?
-    static ref THEME_SET: ThemeSet = ThemeSet::load_defaults();
-    /// Load selected highlighting style
-    static ref THEME: &'static Theme = &THEME_SET.themes[&CONFIG.syntax_theme];
-    /// Load syntax sets
-    static ref SYNTAX_SET: SyntaxSet = SyntaxSet::load_defaults_newlines();
+    /// Load theme sets (from `CONFIG.syntax_cache_folder` if it's set)
+    static ref THEME_SET: ThemeSet = load_cached("themes", ThemeSet::load_defaults, |themes: &ThemeSet| {
+        !themes.themes.is_empty()
+    });
+    /// Name of the selected highlighting style, can be changed at runtime with `cycle_theme`
+    static ref THEME_NAME: RwLock<String> = RwLock::new(CONFIG.syntax_theme.clone());
+    /// true => datetimes are shown relative to now, can be flipped at runtime with `toggle_relative_dates`
+    static ref RELATIVE_DATES: RwLock<bool> = RwLock::new(CONFIG.relative_dates);
+    /// Load syntax sets (from `CONFIG.syntax_cache_folder` if it's set)
+    static ref SYNTAX_SET: SyntaxSet = load_cached(
+        "syntaxes",
+        SyntaxSet::load_defaults_newlines,
+        |syntaxes: &SyntaxSet| syntaxes.find_syntax_by_extension("markdown").is_some()
+    );
     /// Load markdown syntax set
     static ref MD_SYNTAX: &'static SyntaxReference =
         SYNTAX_SET.find_syntax_by_extension("markdown").unwrap();
 }
 
+/// Reads a theme/syntax set from its dump in `CONFIG.syntax_cache_folder`
+/// If there's no folder set, it's built from scratch with `load_defaults`
+/// A dump that's missing, can't be read, doesn't pass `is_valid`, or was written by
+/// another version of gooseberry is rebuilt and written again
+fn load_cached<T: Serialize + DeserializeOwned>(
+    name: &str,
+    load_defaults: fn() -> T,
+    is_valid: fn(&T) -> bool,
+) -> T {
+    let folder = match &CONFIG.syntax_cache_folder {
+        Some(folder) => Path::new(folder),
+        None => return load_defaults(),
+    };
+    let path = folder.join(format!("{}-{}.packdump", name, env!("CARGO_PKG_VERSION")));
+    if let Ok(cached) = from_dump_file::<T, _>(&path) {
+        if is_valid(&cached) {
+            return cached;
+        }
+    }
+    let loaded = load_defaults();
+    // no cache just means a slower start next time
+    if fs::create_dir_all(folder).is_ok() {
+        dump_to_file(&loaded, &path).ok();
+    }
+    loaded
+}
+
+/// Names of all the highlighting styles there are, sorted
+pub fn theme_names() -> Vec<String> {
+    let mut names = THEME_SET.themes.keys().cloned().collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+/// Name of the highlighting style currently in use
+pub fn current_theme() -> String {
+    THEME_NAME.read().unwrap().clone()
+}
+
+/// Switch to the next theme in `CONFIG.syntax_themes` (wraps around)
+/// Themes that aren't in the default ThemeSet are skipped
+/// Returns the name of the new theme
+pub fn cycle_theme() -> String {
+    let mut theme_name = THEME_NAME.write().unwrap();
+    let themes = CONFIG
+        .syntax_themes
+        .iter()
+        .filter(|t| THEME_SET.themes.contains_key(*t))
+        .collect::<Vec<_>>();
+    if !themes.is_empty() {
+        let next = match themes.iter().position(|t| **t == *theme_name) {
+            Some(index) => (index + 1) % themes.len(),
+            None => 0,
+        };
+        *theme_name = themes[next].clone();
+    }
+    theme_name.clone()
+}
+
+/// Whether datetimes are currently shown relative to now
+pub fn relative_dates() -> bool {
+    *RELATIVE_DATES.read().unwrap()
+}
+
+/// Switch between relative and absolute datetimes, returns true if they're now relative
+pub fn toggle_relative_dates() -> bool {
+    let mut relative = RELATIVE_DATES.write().unwrap();
+    *relative = !*relative;
+    *relative
+}
+
+/// Load the selected highlighting style
+fn theme() -> &'static Theme {
+    &THEME_SET.themes[&*THEME_NAME.read().unwrap()]
+}
+
 /// Convert from `syntect`'s FontStyle to `tui`'s Modifier
 /// Reminder: `tui` doesn't have some of the options
 fn syntect_to_tui_modifier(syntect_modifier: FontStyle) -> Modifier {
@@ -82,11 +176,11 @@ fn syntect_to_tui_modifier(syntect_modifier: FontStyle) -> Modifier {
 }
 
 /// Convert a markdown-formatted string to a list of `tui` Text::styled objects
-pub fn markdown_to_styled_texts(markdown_text: &str) -> Vec<Text> {
+pub fn markdown_to_styled_texts<'a>(markdown_text: &str) -> Vec<Text<'a>> {
     let mut styled_texts = Vec::new();
-    let mut highlighter = HighlightLines::new(&MD_SYNTAX, &THEME);
-    for line in LinesWithEndings::from(&markdown_text) {
-        for (syn_style, text) in highlighter.highlight(&line, &SYNTAX_SET) {
+    let mut highlighter = HighlightLines::new(&MD_SYNTAX, theme());
+    for line in LinesWithEndings::from(markdown_text) {
+        for (syn_style, text) in highlighter.highlight(line, &SYNTAX_SET) {
             styled_texts.push(Text::styled(
                 text.to_string(),
                 syntect_to_tui_style(syn_style),
@@ -97,7 +191,7 @@ pub fn markdown_to_styled_texts(markdown_text: &str) -> Vec<Text> {
 }
 
 /// Add Modifier::DIM to each Text
-fn dim(markdown: Vec<Text>) -> Vec<Text> {
+pub(crate) fn dim(markdown: Vec<Text>) -> Vec<Text> {
     markdown
         .into_iter()
         .map(|styled_text| match styled_text {
@@ -110,6 +204,27 @@ fn dim(markdown: Vec<Text>) -> Vec<Text> {
         .collect()
 }
 
+/// Number of rows a list of Texts takes up when wrapped to `width` columns
+/// (a bit low if word wrapping pushes words to the next row early)
+pub fn count_wrapped_lines(texts: &[Text], width: u16) -> usize {
+    let width = width.max(1) as usize;
+    let mut text = String::new();
+    for styled_text in texts {
+        match styled_text {
+            Text::Styled(t, _) | Text::Raw(t) => text.push_str(t),
+        }
+    }
+    let mut lines = text.split('\n').collect::<Vec<_>>();
+    // a trailing newline doesn't start another row
+    if lines.last() == Some(&"") {
+        lines.pop();
+    }
+    lines
+        .iter()
+        .map(|line| line.width().div_ceil(width).max(1))
+        .sum()
+}
+
 /// Convert `syntect`'s Style to `tui`'s Style
 fn syntect_to_tui_style(syntect_style: SyntectStyle) -> TuiStyle {
     TuiStyle {
@@ -127,34 +242,388 @@ fn syntect_to_tui_style(syntect_style: SyntectStyle) -> TuiStyle {
     }
 }
 
-/// Add Style to a title with optional Task state
-fn style_title(
-    id: u64,
+/// How ids are padded so they line up in the list
+#[derive(Copy, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum IdPadding {
+    /// Plain numbers
+    None,
+    /// Right-aligned with spaces to the width of the largest id
+    Spaces,
+    /// Zero-padded to the width of the largest id (e.g. 007)
+    Zeros,
+}
+
+/// Order of day, month, and year when showing dates (files always use the same format)
+#[derive(Copy, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DateOrder {
+    /// e.g. 25 Dec 2019
+    Dmy,
+    /// e.g. Dec 25 2019
+    Mdy,
+    /// e.g. 2019 Dec 25
+    Ymd,
+}
+
+impl DateOrder {
+    /// chrono format string for a full date
+    fn date_format(self) -> &'static str {
+        match self {
+            DateOrder::Dmy => "%d %b %Y",
+            DateOrder::Mdy => "%b %d %Y",
+            DateOrder::Ymd => "%Y %b %d",
+        }
+    }
+
+    /// chrono format string for a date without the year
+    fn short_date_format(self) -> &'static str {
+        match self {
+            DateOrder::Dmy => "%d %b",
+            DateOrder::Mdy | DateOrder::Ymd => "%b %d",
+        }
+    }
+}
+
+/// How much of each entry the list shows
+#[derive(Copy, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DisplayMode {
+    /// Everything
+    Full,
+    /// Title, date and tags
+    Fold,
+    /// One line with the title and a short date
+    Compact,
+}
+
+impl DisplayMode {
+    /// Full -> Fold -> Compact -> Full
+    pub fn next(self) -> Self {
+        match self {
+            DisplayMode::Full => DisplayMode::Fold,
+            DisplayMode::Fold => DisplayMode::Compact,
+            DisplayMode::Compact => DisplayMode::Full,
+        }
+    }
+}
+
+/// How an entry's title is emphasized
+#[derive(Copy, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TitleStyle {
+    pub bold: bool,
+    pub italic: bool,
+    pub underlined: bool,
+}
+
+impl TitleStyle {
+    fn modifier(self) -> Modifier {
+        let mut modifier = Modifier::empty();
+        if self.bold {
+            modifier |= Modifier::BOLD;
+        }
+        if self.italic {
+            modifier |= Modifier::ITALIC;
+        }
+        if self.underlined {
+            modifier |= Modifier::UNDERLINED;
+        }
+        modifier
+    }
+}
+
+/// Lines of metadata shown above an entry's description
+#[derive(Copy, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MetadataLine {
+    /// ID, Task state, and title
+    Title,
+    /// Tags on the left, datetime on the right
+    DateTags,
+    /// Event people, only in full display
+    People,
+}
+
+/// Title, then date and tags, then people
+pub const DEFAULT_METADATA_ORDER: &[MetadataLine] =
+    &[MetadataLine::Title, MetadataLine::DateTags, MetadataLine::People];
+
+/// Things in an entry's description/notes worth showing at a glance in the list
+#[derive(Copy, Debug, Clone, Default, PartialEq)]
+pub struct BodyFeatures {
+    /// markdown images, `![alt](path)`
+    pub attachments: usize,
+    /// web addresses (http:// or https://)
+    pub links: usize,
+    /// checklist items (`- [ ]` or `- [x]`)
+    pub subtasks: usize,
+    /// checked checklist items
+    pub subtasks_done: usize,
+}
+
+impl BodyFeatures {
+    /// Counts attachments, links and checklist items in markdown text
+    pub fn from_markdown(text: &str) -> Self {
+        let mut features = Self {
+            attachments: text.matches("![").count(),
+            links: text.matches("http://").count() + text.matches("https://").count(),
+            ..Self::default()
+        };
+        for line in text.lines() {
+            let line = line.trim_start();
+            let item = line
+                .strip_prefix("- ")
+                .or_else(|| line.strip_prefix("* "))
+                .or_else(|| line.strip_prefix("+ "));
+            if let Some(item) = item {
+                if item.starts_with("[ ]") {
+                    features.subtasks += 1;
+                } else if item.starts_with("[x]") || item.starts_with("[X]") {
+                    features.subtasks += 1;
+                    features.subtasks_done += 1;
+                }
+            }
+        }
+        features
+    }
+
+    /// true => nothing to show
+    pub fn is_empty(&self) -> bool {
+        self.attachments == 0 && self.links == 0 && self.subtasks == 0
+    }
+}
+
+/// Layout information for displaying an entry in a list
+#[derive(Copy, Debug, Clone)]
+pub struct DisplayFormat {
+    /// Width available for a line
+    pub terminal_width: u16,
+    /// Number of digits in the largest id of the list
+    pub id_width: usize,
+    /// true => the entry has changes that aren't written to file
+    pub unsaved: bool,
+    /// true => the title is rendered as markdown instead of plain italics
+    pub markdown_title: bool,
+    /// true => one line per entry
+    pub compact: bool,
+    /// color of the title instead of the default, e.g. for overdue entries
+    pub title_color: Option<TuiColor>,
+    /// which metadata lines to show and in what order (ignored in compact mode)
+    pub metadata_order: &'static [MetadataLine],
+    /// shown as a row of glyphs after the metadata (not in compact mode)
+    pub body_features: BodyFeatures,
+    /// how the title is emphasized, see `CONFIG.title_styles`
+    pub title_style: TitleStyle,
+    /// true => only the date is shown, not the time
+    pub date_only: bool,
+    /// true => only the time is shown, not the date (when a date header says the day)
+    pub time_only: bool,
+}
+
+impl DisplayFormat {
+    pub fn new(terminal_width: u16, max_id: u64) -> Self {
+        Self {
+            terminal_width,
+            id_width: max_id.to_string().len(),
+            unsaved: false,
+            markdown_title: false,
+            compact: false,
+            title_color: None,
+            metadata_order: DEFAULT_METADATA_ORDER,
+            body_features: BodyFeatures::default(),
+            title_style: TitleStyle {
+                bold: true,
+                italic: true,
+                underlined: false,
+            },
+            date_only: false,
+            time_only: false,
+        }
+    }
+
+    /// Same layout for an entry with or without unsaved changes
+    pub fn with_unsaved(self, unsaved: bool) -> Self {
+        Self { unsaved, ..self }
+    }
+
+    /// Same layout in one line or in full
+    pub fn with_compact(self, compact: bool) -> Self {
+        Self { compact, ..self }
+    }
+
+    /// Same layout with or without a markdown-rendered title
+    pub fn with_markdown_title(self, markdown_title: bool) -> Self {
+        Self {
+            markdown_title,
+            ..self
+        }
+    }
+
+    /// Same layout with a differently colored title (None => default color)
+    pub fn with_title_color(self, title_color: Option<TuiColor>) -> Self {
+        Self {
+            title_color,
+            ..self
+        }
+    }
+
+    /// Same layout with metadata lines in a different order
+    pub fn with_metadata_order(self, metadata_order: &'static [MetadataLine]) -> Self {
+        Self {
+            metadata_order,
+            ..self
+        }
+    }
+
+    /// Same layout for an entry with different attachments/links/subtasks
+    pub fn with_body_features(self, body_features: BodyFeatures) -> Self {
+        Self {
+            body_features,
+            ..self
+        }
+    }
+
+    /// Same layout with a differently emphasized title
+    pub fn with_title_style(self, title_style: TitleStyle) -> Self {
+        Self {
+            title_style,
+            ..self
+        }
+    }
+
+    /// Same layout showing only the date of the datetime
+    pub fn with_date_only(self) -> Self {
+        Self {
+            date_only: true,
+            ..self
+        }
+    }
+
+    /// Same layout showing only the time of the datetime
+    pub fn with_time_only(self) -> Self {
+        Self {
+            time_only: true,
+            ..self
+        }
+    }
+
+    /// Pads an id according to `CONFIG.id_padding`
+    fn format_id(self, id: u64) -> String {
+        match CONFIG.id_padding {
+            IdPadding::None => id.to_string(),
+            IdPadding::Spaces => format!("{:>width$}", id, width = self.id_width),
+            IdPadding::Zeros => format!("{:0width$}", id, width = self.id_width),
+        }
+    }
+}
+
+/// Add Style to a title with an optional mark in front (usually the Task state's symbol)
+/// `right_text` (usually the ID) is aligned to the right, whatever the width of the mark
+fn style_title<'a>(
     title: &str,
-    mark: Option<TaskState>,
-    terminal_width: u16,
-    bold: bool,
-) -> Vec<Text> {
+    right_text: &str,
+    mark: Option<Text<'a>>,
+    format: DisplayFormat,
+) -> Vec<Text<'a>> {
     let mut texts = Vec::new();
-    let mut terminal_width = terminal_width;
-    if let Some(state) = mark {
-        texts.push(state.styled_symbol());
-        terminal_width -= 2;
+    let mut terminal_width = format.terminal_width;
+    if format.unsaved {
+        let marker = format!("{} ", CONFIG.unsaved_marker);
+        terminal_width = terminal_width.saturating_sub(marker.width() as u16);
+        texts.push(Text::styled(
+            marker,
+            TuiStyle::default().modifier(Modifier::DIM),
+        ));
+    }
+    if let Some(mark) = mark {
+        let mark_width = match &mark {
+            Text::Styled(t, _) | Text::Raw(t) => t.width(),
+        };
+        terminal_width = terminal_width.saturating_sub(mark_width as u16);
+        texts.push(mark);
     }
-    let modifier = if bold {
-        Modifier::ITALIC | Modifier::BOLD
+    let mut style = TuiStyle::default().modifier(format.title_style.modifier());
+    if let Some(color) = format.title_color {
+        style = style.fg(color);
+    }
+    if format.markdown_title {
+        // markdown keeps to one line, the id goes on the right (or on the next line if it doesn't fit)
+        let (title, id) = (title.trim(), right_text);
+        let title_width = title.width() as u16;
+        let padded_id = if (title_width as usize) + id.width() + 1 > terminal_width as usize {
+            format!("\n{}", right_format("", id, terminal_width, true))
+        } else {
+            right_format("", id, terminal_width.saturating_sub(title_width), true)
+        };
+        texts.extend(markdown_to_styled_texts(title));
+        texts.push(Text::styled(padded_id, style));
     } else {
-        Modifier::ITALIC
-    };
-    texts.push(Text::styled(
-        right_format(title.trim(), &format!("{}", id), terminal_width, false),
-        TuiStyle::default().modifier(modifier),
-    ));
+        texts.push(Text::styled(
+            right_format(title.trim(), right_text, terminal_width, false),
+            style,
+        ));
+    }
     texts
 }
 
-fn format_date(date: Date<Utc>) -> String {
-    format!("{}", date.format("%b %d %Y"))
+/// `CONFIG.soon_color` if the time is coming up within `CONFIG.soon_hours`
+pub fn soon_color(datetime: &DateTime<Utc>) -> Option<TuiColor> {
+    let until = *datetime - Utc::now();
+    if until >= Duration::zero() && until <= Duration::hours(CONFIG.soon_hours) {
+        Some(CONFIG.soon_color)
+    } else {
+        None
+    }
+}
+
+/// `CONFIG.overdue_color` if a deadline has passed, otherwise same as `soon_color`
+pub fn deadline_color(deadline: &DateTime<Utc>) -> Option<TuiColor> {
+    if *deadline < Utc::now() {
+        Some(CONFIG.overdue_color)
+    } else {
+        soon_color(deadline)
+    }
+}
+
+/// Cuts text down to a display width, ending with … if anything was cut
+fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_owned();
+    }
+    let mut truncated = String::new();
+    let mut truncated_width = 0;
+    for c in text.chars() {
+        let c_width = c.width().unwrap_or(0);
+        if truncated_width + c_width + 1 > width {
+            break;
+        }
+        truncated.push(c);
+        truncated_width += c_width;
+    }
+    truncated.push('…');
+    truncated
+}
+
+/// Style an entry on a single line
+/// <task state> First line of the title     Date ID
+fn style_compact<'a>(
+    id: u64,
+    title: &str,
+    mark: Option<TaskState>,
+    datetime: &DateTime<Utc>,
+    format: DisplayFormat,
+) -> Vec<Text<'a>> {
+    let right_text = format!(
+        "{} {}",
+        datetime.format(CONFIG.date_order.short_date_format()),
+        format.format_id(id)
+    );
+    let mut title_width = (format.terminal_width as usize).saturating_sub(right_text.width() + 1);
+    if let Some(state) = mark {
+        title_width = title_width.saturating_sub(state.marker_width());
+    }
+    let title = truncate(title.trim().lines().next().unwrap_or(""), title_width);
+    style_title(&title, &right_text, mark.map(TaskState::styled_symbol), format)
+}
+
+fn format_date(date: NaiveDate) -> String {
+    format!("{}", date.format(CONFIG.date_order.date_format()))
 }
 
 fn format_time(time: NaiveTime) -> String {
@@ -162,14 +631,61 @@ fn format_time(time: NaiveTime) -> String {
 }
 
 fn format_datetime(datetime: DateTime<Utc>) -> String {
-    format!("{}", datetime.format("%r %a %b %d %Y"))
+    format!(
+        "{}",
+        datetime.format(&format!("%r %a {}", CONFIG.date_order.date_format()))
+    )
 }
 
-pub(crate) fn style_people(people: &[String]) -> Text {
-    Text::styled(
-        format!("{}\n", people.join(", ")),
-        TuiStyle::default().fg(CONFIG.secondary_metadata_color),
-    )
+/// How far from now a datetime is
+/// e.g. "just now", "5 minutes ago", "in 2 days"
+fn format_relative(datetime: DateTime<Utc>) -> String {
+    let difference = Utc::now() - datetime;
+    let (future, difference) = if difference < Duration::zero() {
+        (true, -difference)
+    } else {
+        (false, difference)
+    };
+    let (amount, unit) = if difference < Duration::minutes(1) {
+        return "just now".into();
+    } else if difference < Duration::hours(1) {
+        (difference.num_minutes(), "minute")
+    } else if difference < Duration::days(1) {
+        (difference.num_hours(), "hour")
+    } else if difference < Duration::days(60) {
+        (difference.num_days(), "day")
+    } else if difference < Duration::days(730) {
+        (difference.num_days() / 30, "month")
+    } else {
+        (difference.num_days() / 365, "year")
+    };
+    let plural = if amount == 1 { "" } else { "s" };
+    if future {
+        format!("in {} {}{}", amount, unit, plural)
+    } else {
+        format!("{} {}{} ago", amount, unit, plural)
+    }
+}
+
+/// One line per role, e.g. `presenter: Alice, Carol`
+/// People without a role (None) are listed without a label
+pub(crate) fn style_people<'a>(people_by_role: &[(Option<&str>, Vec<&str>)]) -> Vec<Text<'a>> {
+    let mut texts = Vec::new();
+    for (role, names) in people_by_role {
+        if let Some(role) = role {
+            texts.push(Text::styled(
+                format!("{}: ", role),
+                TuiStyle::default()
+                    .fg(CONFIG.primary_metadata_color)
+                    .modifier(Modifier::BOLD),
+            ));
+        }
+        texts.push(Text::styled(
+            format!("{}\n", names.join(", ")),
+            TuiStyle::default().fg(CONFIG.secondary_metadata_color),
+        ));
+    }
+    texts
 }
 
 /// Style datetime and tags on same line, tags on left, date on right
@@ -180,13 +696,23 @@ fn style_datetime_tags<'a>(
     date_only: bool,
     time_only: bool,
 ) -> Text<'a> {
-    let datetime_formatted = if date_only {
-        format_date(datetime.date())
+    let datetime_formatted = if relative_dates() {
+        format_relative(*datetime)
+    } else if date_only {
+        format_date(datetime.date_naive())
     } else if time_only {
         format_time(datetime.time())
     } else {
         format_datetime(*datetime)
     };
+    let mut tags = tags.iter().map(|t| t.as_str()).collect::<Vec<_>>();
+    if CONFIG.sort_tags {
+        tags.sort_by_key(|t| t.to_lowercase());
+    }
+    if CONFIG.dedup_tags_display {
+        let mut seen = HashSet::new();
+        tags.retain(|t| seen.insert(t.to_lowercase()));
+    }
     Text::styled(
         right_format(&tags.join(","), &datetime_formatted, terminal_width, true),
         TuiStyle::default().fg(CONFIG.primary_metadata_color),
@@ -197,6 +723,8 @@ fn style_datetime_tags<'a>(
 /// ID <task state> Title
 /// Date Time
 /// Tags
+/// People (grouped by role, if any are given)
+/// in the order given by `format.metadata_order`
 /// TODO: this is getting ugly, make some format structs
 pub(crate) fn style_short<'a>(
     id: u64,
@@ -204,24 +732,96 @@ pub(crate) fn style_short<'a>(
     mark: Option<TaskState>,
     datetime: &'a DateTime<Utc>,
     tags: &'a [String],
-    terminal_width: u16,
-    date_only: bool,
-    time_only: bool,
-    bold_title: bool,
+    people_by_role: &[(Option<&str>, Vec<&str>)],
+    format: DisplayFormat,
 ) -> Vec<Text<'a>> {
-    let mut texts = style_title(id, title, mark, terminal_width, bold_title);
-    texts.push(style_datetime_tags(
-        datetime,
-        tags,
-        terminal_width,
-        date_only,
-        time_only,
-    ));
+    if format.compact {
+        return style_compact(id, title, mark, datetime, format);
+    }
+    let mut texts = Vec::new();
+    for line in format.metadata_order {
+        match line {
+            MetadataLine::Title => texts.extend(style_title(
+                title,
+                &format.format_id(id),
+                mark.map(TaskState::styled_symbol),
+                format,
+            )),
+            MetadataLine::DateTags => texts.push(style_datetime_tags(
+                datetime,
+                tags,
+                format.terminal_width,
+                format.date_only,
+                format.time_only,
+            )),
+            MetadataLine::People => texts.extend(style_people(people_by_role)),
+        }
+    }
+    if let Some(glyphs) = style_body_features(format.body_features) {
+        texts.push(glyphs);
+    }
     texts
 }
 
+/// `<label>: <value>` line, in `color` if given
+pub(crate) fn style_field<'a>(label: &str, value: String, color: Option<TuiColor>) -> Text<'a> {
+    Text::styled(
+        format!("{}: {}\n", label, value),
+        TuiStyle::default().fg(color.unwrap_or(CONFIG.secondary_metadata_color)),
+    )
+}
+
+/// `Due: <date>` line for a Task, in `color` if given
+pub(crate) fn style_due<'a>(due: DateTime<Utc>, color: Option<TuiColor>) -> Text<'a> {
+    let due_formatted = if relative_dates() {
+        format_relative(due)
+    } else {
+        format_datetime(due)
+    };
+    style_field("Due", due_formatted, color)
+}
+
+/// `2:00 PM – 3:30 PM` line for an Event, with the end's day too if it ends on another day
+pub(crate) fn style_time_range<'a>(start: DateTime<Utc>, end: DateTime<Utc>) -> Text<'a> {
+    let end_formatted = if end.date_naive() == start.date_naive() {
+        end.format("%-I:%M %p").to_string()
+    } else {
+        format!("{} {}", end.format("%-I:%M %p"), format_date(end.date_naive()))
+    };
+    Text::styled(
+        format!("{} – {}\n", start.format("%-I:%M %p"), end_formatted),
+        TuiStyle::default().fg(CONFIG.secondary_metadata_color),
+    )
+}
+
+/// Glyphs (from `CONFIG.body_glyphs`) with counts for an entry's attachments, links and subtasks
+/// e.g. `📎 1  ☑ 2/5`, None if it has none of them
+fn style_body_features<'a>(features: BodyFeatures) -> Option<Text<'a>> {
+    if features.is_empty() {
+        return None;
+    }
+    let glyphs = &CONFIG.body_glyphs;
+    let mut parts = Vec::new();
+    if features.attachments > 0 {
+        parts.push(format!("{} {}", glyphs.attachment, features.attachments));
+    }
+    if features.links > 0 {
+        parts.push(format!("{} {}", glyphs.link, features.links));
+    }
+    if features.subtasks > 0 {
+        parts.push(format!(
+            "{} {}/{}",
+            glyphs.subtask, features.subtasks_done, features.subtasks
+        ));
+    }
+    Some(Text::styled(
+        format!("{}\n", parts.join("  ")),
+        TuiStyle::default().fg(CONFIG.secondary_metadata_color),
+    ))
+}
+
 pub(crate) fn style_date_num_entries<'a>(
-    date: Date<Utc>,
+    date: NaiveDate,
     num_entries: usize,
     terminal_width: u16,
 ) -> Text<'a> {
@@ -239,21 +839,88 @@ pub(crate) fn style_date_num_entries<'a>(
     )
 }
 
-/// Add a fake cursor
-/// Couldn't figure out how to get the real cursor where we need it
-/// TODO: this probably won't work (i.e. will break up words) if you add arrow key movement
-pub(crate) fn cursor<'a>() -> Text<'a> {
-    Text::Styled(
-        CONFIG.cursor_char.to_string().into(),
-        TuiStyle::default()
-            .fg(CONFIG.cursor_color)
-            .modifier(Modifier::BOLD),
+/// Splits out the parts of the texts that match `query` (ignoring case) and shows them reversed
+/// Texts where lowercasing changes the length (some non-ASCII letters) aren't highlighted
+pub fn highlight_matches<'a>(texts: Vec<Text<'a>>, query: &str) -> Vec<Text<'a>> {
+    let query = query.to_lowercase();
+    if query.is_empty() {
+        return texts;
+    }
+    let mut highlighted = Vec::with_capacity(texts.len());
+    for styled_text in texts {
+        let (text, style) = match &styled_text {
+            Text::Styled(text, style) => (text.to_string(), *style),
+            Text::Raw(text) => (text.to_string(), TuiStyle::default()),
+        };
+        let lowercase = text.to_lowercase();
+        if lowercase.len() != text.len() || !lowercase.contains(&query) {
+            highlighted.push(styled_text);
+            continue;
+        }
+        let mut start = 0;
+        for (index, _) in lowercase.match_indices(&query) {
+            let end = index + query.len();
+            if !text.is_char_boundary(index) || !text.is_char_boundary(end) {
+                continue;
+            }
+            if index > start {
+                highlighted.push(Text::Styled(text[start..index].to_owned().into(), style));
+            }
+            highlighted.push(Text::Styled(
+                text[index..end].to_owned().into(),
+                style.modifier(style.modifier | Modifier::REVERSED),
+            ));
+            start = end;
+        }
+        if start < text.len() {
+            highlighted.push(Text::Styled(text[start..].to_owned().into(), style));
+        }
+    }
+    highlighted
+}
+
+/// A dimmed line of `divider` across the terminal, to separate entries
+pub(crate) fn style_divider<'a>(divider: char, terminal_width: u16) -> Text<'a> {
+    let count = terminal_width as usize / divider.width().unwrap_or(1).max(1);
+    Text::styled(
+        format!("{}\n", divider.to_string().repeat(count)),
+        TuiStyle::default().modifier(Modifier::DIM),
     )
 }
 
+/// How the fake cursor in the input boxes is drawn
+#[derive(Copy, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CursorStyle {
+    /// the character under the cursor in reverse video (a space at the end)
+    Block,
+    /// `CONFIG.cursor_char` (`|` by default)
+    Bar,
+    /// `_`
+    Underscore,
+}
+
+/// Add a fake cursor, for when the terminal's cursor can't be moved where we need it
+/// `under` is the character at the cursor, only drawn (reversed) by `CursorStyle::Block`
+/// The other styles push the rest of the line over by one
+pub(crate) fn cursor<'a>(under: Option<char>) -> Text<'a> {
+    let style = TuiStyle::default().fg(CONFIG.cursor_color);
+    match CONFIG.cursor_style {
+        CursorStyle::Block => Text::Styled(
+            under.unwrap_or(' ').to_string().into(),
+            style.modifier(Modifier::REVERSED),
+        ),
+        CursorStyle::Bar => Text::Styled(
+            CONFIG.cursor_char.to_string().into(),
+            style.modifier(Modifier::BOLD),
+        ),
+        CursorStyle::Underscore => Text::Styled("_".into(), style.modifier(Modifier::BOLD)),
+    }
+}
+
 /// Adds new_text to an existing text but on the right. If there's not enough
 /// space in the terminal to do that with at least one space in the middle
 /// then puts the new_text on the next line (on the left if left_too_long else right)
+/// Never panics on narrow terminals, it just wraps badly
 fn right_format(text: &str, new_text: &str, terminal_width: u16, left_too_long: bool) -> String {
     let terminal_width = terminal_width as usize;
     let text_len = text.width();
@@ -269,7 +936,7 @@ fn right_format(text: &str, new_text: &str, terminal_width: u16, left_too_long:
             )
         }
     } else {
-        let num_spaces = terminal_width - text_len - new_text_len;
+        let num_spaces = terminal_width.saturating_sub(text_len + new_text_len);
         format!(
             "{}{}{}\n",
             text,
@@ -281,3 +948,157 @@ fn right_format(text: &str, new_text: &str, terminal_width: u16, left_too_long:
         )
     }
 }
+
+/// ANSI color code number for a tui Color, None for the terminal's default
+/// `background` => code for the background instead of the foreground
+fn ansi_color_code(color: TuiColor, background: bool) -> Option<String> {
+    let offset = if background { 10 } else { 0 };
+    let extended = if background { 48 } else { 38 };
+    let code = match color {
+        TuiColor::Reset => return None,
+        TuiColor::Black => 30 + offset,
+        TuiColor::Red => 31 + offset,
+        TuiColor::Green => 32 + offset,
+        TuiColor::Yellow => 33 + offset,
+        TuiColor::Blue => 34 + offset,
+        TuiColor::Magenta => 35 + offset,
+        TuiColor::Cyan => 36 + offset,
+        TuiColor::Gray => 37 + offset,
+        TuiColor::DarkGray => 90 + offset,
+        TuiColor::LightRed => 91 + offset,
+        TuiColor::LightGreen => 92 + offset,
+        TuiColor::LightYellow => 93 + offset,
+        TuiColor::LightBlue => 94 + offset,
+        TuiColor::LightMagenta => 95 + offset,
+        TuiColor::LightCyan => 96 + offset,
+        TuiColor::White => 97 + offset,
+        TuiColor::Rgb(r, g, b) => return Some(format!("{};2;{};{};{}", extended, r, g, b)),
+        TuiColor::Indexed(i) => return Some(format!("{};5;{}", extended, i)),
+    };
+    Some(code.to_string())
+}
+
+/// ANSI escape sequence that switches to a tui Style, empty for the default style
+fn ansi_style(style: TuiStyle) -> String {
+    let mut codes = Vec::new();
+    for (modifier, code) in &[
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::CROSSED_OUT, "9"),
+    ] {
+        if style.modifier.contains(*modifier) {
+            codes.push(code.to_string());
+        }
+    }
+    codes.extend(ansi_color_code(style.fg, false));
+    codes.extend(ansi_color_code(style.bg, true));
+    if codes.is_empty() {
+        String::new()
+    } else {
+        format!("\x1b[{}m", codes.join(";"))
+    }
+}
+
+/// Turns styled Texts into a string for printing to a terminal
+/// `color` => styles become ANSI escape codes, otherwise just the plain text
+pub fn texts_to_ansi(texts: &[Text], color: bool) -> String {
+    let mut output = String::new();
+    for styled_text in texts {
+        match styled_text {
+            Text::Raw(text) => output.push_str(text),
+            Text::Styled(text, style) => {
+                let escape = ansi_style(*style);
+                if color && !escape.is_empty() {
+                    // reset at each line end so pagers don't carry styles over
+                    for (i, line) in text.split('\n').enumerate() {
+                        if i > 0 {
+                            output.push('\n');
+                        }
+                        if !line.is_empty() {
+                            output.push_str(&format!("{}{}\x1b[0m", escape, line));
+                        }
+                    }
+                } else {
+                    output.push_str(text);
+                }
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn dates_in_each_order() {
+        let christmas = Utc.with_ymd_and_hms(2019, 12, 25, 10, 0, 0).unwrap();
+        let orders = [
+            (DateOrder::Dmy, "25 Dec 2019", "25 Dec"),
+            (DateOrder::Mdy, "Dec 25 2019", "Dec 25"),
+            (DateOrder::Ymd, "2019 Dec 25", "Dec 25"),
+        ];
+        for (order, date, short_date) in &orders {
+            assert_eq!(christmas.format(order.date_format()).to_string(), *date);
+            assert_eq!(
+                christmas.format(order.short_date_format()).to_string(),
+                *short_date
+            );
+        }
+    }
+
+    /// All the Texts joined into one string
+    fn joined(texts: &[Text]) -> String {
+        texts
+            .iter()
+            .map(|text| match text {
+                Text::Styled(t, _) | Text::Raw(t) => t.to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn narrow_terminal_does_not_panic() {
+        let datetime = Utc.with_ymd_and_hms(2019, 12, 25, 10, 0, 0).unwrap();
+        let tags = vec!["shopping".to_string()];
+        let people = vec![(None, vec!["bob"])];
+        for width in 0..4 {
+            let mut format = DisplayFormat::new(width, 100);
+            format.unsaved = true;
+            let texts = style_short(
+                100,
+                "Buy milk",
+                Some(TaskState::NotDone),
+                &datetime,
+                &tags,
+                &people,
+                format,
+            );
+            assert!(joined(&texts).contains("Buy milk"));
+        }
+    }
+
+    #[test]
+    fn id_stays_right_aligned_after_a_mark() {
+        // no mark, a Task's own mark and a double-width one
+        let wide_mark = "✅ ";
+        assert_eq!(wide_mark.width(), 3);
+        let marks = [
+            None,
+            Some(TaskState::Done.styled_symbol()),
+            Some(Text::raw(wide_mark)),
+        ];
+        for mark in marks {
+            let texts = style_title("Buy milk", "7", mark, DisplayFormat::new(40, 7));
+            let line = joined(&texts);
+            assert!(line.ends_with(" 7\n"));
+            assert_eq!(line.trim_end_matches('\n').width(), 40);
+        }
+    }
+}
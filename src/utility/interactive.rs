@@ -1,18 +1,34 @@
-use std::{sync::mpsc, thread, time::Duration};
+use std::{
+    cell::Cell,
+    collections::HashMap,
+    env, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
+    thread,
+    time::{Duration, SystemTime},
+};
 
 use anyhow::Error;
-use crossterm::{input, InputEvent, KeyEvent};
+use crossterm::{input, InputEvent, KeyEvent, RawScreen};
 use crossterm::TerminalCursor;
 use tui::{
     backend::CrosstermBackend,
     Frame,
-    layout::{Constraint, Rect},
+    layout::{Constraint, Direction, Layout, Rect},
     widgets::{Block, Borders, Paragraph, Text, Widget},
 };
-use unicode_width::UnicodeWidthChar;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 use crate::app::{HELP_BOX_PERCENT, TAB_BOX_PERCENT};
+use crate::errors::Sorry;
 use crate::utility;
+use crate::utility::config::CONFIG;
+use crate::utility::formatting::CursorStyle;
 
 pub type TuiFrame<'a> = Frame<'a, CrosstermBackend>;
 
@@ -26,6 +42,9 @@ pub struct InputBoxes {
     index: usize,
 }
 
+/// Checks a box's (non-empty) content, Err says what's wrong with it
+pub type Check = fn(&str) -> Result<(), String>;
+
 #[derive(Debug, Clone)]
 pub struct InputBox {
     /// Written on top of the box
@@ -34,6 +53,10 @@ pub struct InputBox {
     is_writing: bool,
     /// growing content of the box
     content: String,
+    /// where typing goes, a byte index into `content` (always on a char boundary)
+    cursor: usize,
+    /// column (in characters) that Up/Down try to keep, set by the first Up/Down after moving sideways
+    goal_column: Option<usize>,
     width: usize,
     /// if true, renders markdown, else plain text
     /// TODO: Probably make this more flexible, e.g. code?
@@ -43,6 +66,10 @@ pub struct InputBox {
     percent: u16,
     /// scroll index
     scroll: u16,
+    /// if true, Ctrl-s/Ctrl-w don't save while the box is empty
+    required: bool,
+    /// Ctrl-s/Ctrl-w don't save while this gives an error for the (non-empty) content
+    check: Option<Check>,
 }
 
 impl InputBox {
@@ -52,10 +79,46 @@ impl InputBox {
             title,
             is_writing: false,
             content: String::new(),
+            cursor: 0,
+            goal_column: None,
             width: 0,
             markdown,
             percent,
             scroll: 0,
+            required: false,
+            check: None,
+        }
+    }
+
+    /// Makes the box one that has to be filled in (whitespace doesn't count) before saving
+    pub fn required(self) -> Self {
+        Self {
+            required: true,
+            ..self
+        }
+    }
+
+    /// Makes the box check its content before saving, e.g. that a date can be read
+    pub fn with_check(self, check: Check) -> Self {
+        Self {
+            check: Some(check),
+            ..self
+        }
+    }
+
+    /// Why the box can't be saved as it is, None if it can
+    fn problem(&self) -> Option<String> {
+        let content = self.content.trim();
+        if content.is_empty() {
+            if self.required {
+                Some(format!("{} is empty", self.title))
+            } else {
+                None
+            }
+        } else {
+            self.check
+                .and_then(|check| check(content).err())
+                .map(|error| format!("{}: {}", self.title, error))
         }
     }
 
@@ -76,19 +139,164 @@ impl InputBox {
 
     /// Styles text according to whether self.markdown is true or not
     /// TODO: Again, flexibility
-    /// Also, adds a fake cursor to the end if it's the active box
-    /// Doesn't handle moving around with arrow keys, pretty clunky that way, you have to backspace
-    /// TODO: Switch to ropey and keep an index to deal with this^?
-    fn get_text(&self) -> Vec<Text> {
+    /// Also, adds a fake cursor at the cursor index if it's the active box
+    fn get_text(&self) -> Vec<Text<'_>> {
         let current = if self.markdown {
             utility::formatting::markdown_to_styled_texts(&self.content)
         } else {
             vec![Text::raw(&self.content)]
         };
-        //        if self.is_writing {
-        //            current.push(utility::formatting::cursor());
-        //        }
-        current
+        if self.is_writing && fake_cursor() {
+            insert_cursor(current, self.cursor)
+        } else {
+            current
+        }
+    }
+
+    /// Puts the cursor at the end of the content
+    fn cursor_to_end(&mut self) {
+        self.cursor = self.content.len();
+    }
+
+    /// Adds a character where the cursor is and moves the cursor past it
+    fn insert(&mut self, c: char) {
+        self.content.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+        self.width += c.width().unwrap_or(0);
+    }
+
+    /// Removes the character before the cursor (Backspace)
+    fn delete_before(&mut self) {
+        if let Some(c) = self.content[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+            self.content.remove(self.cursor);
+            self.width -= c.width().unwrap_or(0);
+        }
+    }
+
+    /// Removes the character at the cursor (Delete)
+    fn delete_at(&mut self) {
+        if self.cursor < self.content.len() {
+            let c = self.content.remove(self.cursor);
+            self.width -= c.width().unwrap_or(0);
+        }
+    }
+
+    /// Moves the cursor one character to the left
+    fn cursor_left(&mut self) {
+        if let Some(c) = self.content[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+        }
+    }
+
+    /// Moves the cursor one character to the right
+    fn cursor_right(&mut self) {
+        if let Some(c) = self.content[self.cursor..].chars().next() {
+            self.cursor += c.len_utf8();
+        }
+    }
+
+    /// Moves the cursor to the start of its line
+    fn cursor_home(&mut self) {
+        self.cursor = self.content[..self.cursor]
+            .rfind('\n')
+            .map_or(0, |i| i + 1);
+    }
+
+    /// Moves the cursor to the end of its line
+    fn cursor_end(&mut self) {
+        self.cursor = self.content[self.cursor..]
+            .find('\n')
+            .map_or(self.content.len(), |i| self.cursor + i);
+    }
+
+    /// Moves the cursor to the same column of the line above (or the end of it if it's shorter)
+    /// false if the cursor is already on the first line
+    fn cursor_up(&mut self) -> bool {
+        let line_start = self.content[..self.cursor].rfind('\n').map_or(0, |i| i + 1);
+        if line_start == 0 {
+            return false;
+        }
+        let column = self.goal_column();
+        let above_start = self.content[..line_start - 1].rfind('\n').map_or(0, |i| i + 1);
+        self.cursor = self.index_in_line(above_start, column);
+        true
+    }
+
+    /// Moves the cursor to the same column of the line below (or the end of it if it's shorter)
+    /// false if the cursor is already on the last line
+    fn cursor_down(&mut self) -> bool {
+        let below_start = match self.content[self.cursor..].find('\n') {
+            Some(i) => self.cursor + i + 1,
+            None => return false,
+        };
+        let column = self.goal_column();
+        self.cursor = self.index_in_line(below_start, column);
+        true
+    }
+
+    /// The column Up/Down should go to, the cursor's current one unless it's already set
+    fn goal_column(&mut self) -> usize {
+        let line_start = self.content[..self.cursor].rfind('\n').map_or(0, |i| i + 1);
+        let column = self.content[line_start..self.cursor].chars().count();
+        *self.goal_column.get_or_insert(column)
+    }
+
+    /// Byte index of `column` characters into the line starting at `line_start`,
+    /// stops at the end of the line
+    fn index_in_line(&self, line_start: usize, column: usize) -> usize {
+        self.content[line_start..]
+            .char_indices()
+            .take_while(|(_, c)| *c != '\n')
+            .nth(column)
+            .map_or_else(
+                || {
+                    self.content[line_start..]
+                        .find('\n')
+                        .map_or(self.content.len(), |i| line_start + i)
+                },
+                |(i, _)| line_start + i,
+            )
+    }
+
+    /// Row (counting wrapped lines, ignoring scroll) and column of the cursor in a box `width` wide
+    /// Wraps character by character, so long words can put it a bit off
+    fn cursor_row_column(&self, width: usize) -> (usize, usize) {
+        let mut lines = self.content[..self.cursor].split('\n').collect::<Vec<_>>();
+        let last_line = lines.pop().unwrap_or_default();
+        let rows_before: usize = lines
+            .iter()
+            .map(|line| line.width().div_ceil(width).max(1))
+            .sum();
+        (
+            rows_before + last_line.width() / width,
+            last_line.width() % width,
+        )
+    }
+
+    /// Scrolls just enough to have the cursor in view if the box is drawn in `chunk`
+    fn scroll_to_cursor(&mut self, chunk: Rect) {
+        let width = chunk.width.saturating_sub(2).max(1) as usize;
+        let height = chunk.height.saturating_sub(2).max(1) as usize;
+        let (row, _) = self.cursor_row_column(width);
+        if row < self.scroll as usize {
+            self.scroll = row as u16;
+        } else if row >= self.scroll as usize + height {
+            self.scroll = (row + 1 - height) as u16;
+        }
+    }
+
+    /// Where the cursor is on screen if the box is drawn in `chunk`
+    /// None if it's scrolled out of view
+    fn cursor_position(&self, chunk: Rect) -> Option<(u16, u16)> {
+        let width = chunk.width.saturating_sub(2).max(1) as usize;
+        let height = chunk.height.saturating_sub(2) as usize;
+        let (row, column) = self.cursor_row_column(width);
+        let row = row.checked_sub(self.scroll as usize)?;
+        if row >= height {
+            return None;
+        }
+        Some((chunk.x + 1 + column as u16, chunk.y + 1 + row as u16))
     }
 }
 
@@ -114,11 +322,17 @@ impl InputBoxes {
     }
 
     fn move_cursor(&self, chunks: &[Rect], cursor: &mut TerminalCursor) -> Result<(), Error> {
-        let chunk = chunks[self.index];
-        cursor.goto(1 + chunk.x + self.content_width() as u16, 1 + chunk.y)?;
-        cursor.show()?;
+        if let Some((x, y)) = self.cursor_position(chunks) {
+            cursor.goto(x, y)?;
+        }
         Ok(())
     }
+
+    /// Where the real cursor goes: the cursor of the active box (see `InputBox::cursor_position`)
+    pub fn cursor_position(&self, chunks: &[Rect]) -> Option<(u16, u16)> {
+        self.boxes[self.index].cursor_position(*chunks.get(self.index)?)
+    }
+
     /// Sets the first box to active, and turns the others off (for writing, not rendering)
     /// This should make it so that only one box has the fake cursor
     /// But `\t` seems to break this for some reason
@@ -145,6 +359,8 @@ impl InputBoxes {
     /// TODO: BOUNDS CHECK!!!
     pub fn replace_content(&mut self, index: usize, content: &str) {
         self.boxes[index].content = content.to_owned();
+        self.boxes[index].width = content.width();
+        self.boxes[index].cursor_to_end();
     }
 
     /// Makes layout constraints based on the percentages of each box
@@ -173,14 +389,69 @@ impl InputBoxes {
         self.boxes.is_empty()
     }
 
+    /// true if anything has been written in any of the boxes
+    pub fn has_content(&self) -> bool {
+        self.boxes.iter().any(|b| !b.content.is_empty())
+    }
+
+    /// Box contents as text for saving a draft, each box is
+    /// title
+    /// length of the content in bytes
+    /// content
+    pub fn to_draft(&self) -> String {
+        self.boxes
+            .iter()
+            .map(|b| format!("{}\n{}\n{}\n", b.title, b.content.len(), b.content))
+            .collect()
+    }
+
+    /// Fills the boxes from text made by `to_draft`, matching boxes by title
+    /// Stops at anything that doesn't look right and keeps what was read up to there
+    pub fn load_draft(&mut self, draft: &str) {
+        let mut rest = draft;
+        while let Some(title_end) = rest.find('\n') {
+            let title = &rest[..title_end];
+            rest = &rest[title_end + 1..];
+            let length = match rest.find('\n').map(|i| (rest[..i].parse::<usize>(), i)) {
+                Some((Ok(length), i)) => {
+                    rest = &rest[i + 1..];
+                    length
+                }
+                _ => return,
+            };
+            let content = match rest.get(..length) {
+                Some(content) => content,
+                None => return,
+            };
+            if let Some(input_box) = self.boxes.iter_mut().find(|b| b.title == title) {
+                input_box.content = content.to_owned();
+                input_box.width = content.width();
+                input_box.cursor_to_end();
+            }
+            rest = rest.get(length + 1..).unwrap_or_default();
+        }
+    }
+
+    /// Why the boxes can't be saved yet (empty required boxes, content that fails a check)
+    pub fn problems(&self) -> Vec<String> {
+        self.boxes.iter().filter_map(InputBox::problem).collect()
+    }
+
     /// Return the boxes and clear them out. Stop writing on save
     fn save(&mut self) -> Vec<InputBox> {
         let boxes = self.boxes.clone();
+        self.clear();
+        self.stop_writing();
+        boxes
+    }
+
+    /// Empties every box
+    pub fn clear(&mut self) {
         for i in 0..self.len() {
             self.boxes[i].content = String::new();
+            self.boxes[i].width = 0;
+            self.boxes[i].cursor = 0;
         }
-        self.stop_writing();
-        boxes
     }
 
     /// Go to the next box (wraps around)
@@ -207,12 +478,16 @@ impl InputBoxes {
 
     /// Handle keyboard input events
     /// Ctrl-s: saves the entry being written
+    /// Ctrl-w: saves the entry being written and keeps writing, the boxes keep their content
     /// Ctrl-n: next (next box)
     /// Ctrl-b: back (previous box) TODO: Use next, previous or forward, backward ugh
     /// `\n`: if markdown=false then go to the next box, otherwise it's a normal `\n`
-    /// Backspace: deletes a character
-    /// ^ (Up arrow): scrolls up
-    /// v (Down arrow): scrolls down
+    /// Backspace: deletes the character before the cursor
+    /// Delete: deletes the character at the cursor
+    /// < > (Left/Right arrows): moves the cursor a character
+    /// Home/End: moves the cursor to the start/end of the line
+    /// ^ (Up arrow): moves the cursor up a line, scrolls up if it's already on the first line
+    /// v (Down arrow): moves the cursor down a line, scrolls down if it's already on the last line
     /// Esc: pauses writing mode to go back to scrolling mode.
     ///     Pressing n again resumes writing mode at the same state
     /// Returns (a potential new entry to save, an indicator of whether to stop writing mode)
@@ -222,9 +497,13 @@ impl InputBoxes {
         cursor: &mut TerminalCursor,
         key: KeyEvent,
     ) -> Result<(Option<Vec<InputBox>>, bool), Error> {
+        if key != KeyEvent::Up && key != KeyEvent::Down {
+            self.boxes[self.index].goal_column = None;
+        }
         match key {
             KeyEvent::Ctrl(c) => match c {
                 's' => return Ok((Some(self.save()), true)),
+                'w' => return Ok((Some(self.boxes.clone()), false)),
                 'n' => self.increment_box(chunks, cursor)?,
                 'b' => self.decrement_box(chunks, cursor)?,
                 _ => (),
@@ -233,32 +512,35 @@ impl InputBoxes {
                 if !self.boxes[self.index].markdown && c == '\n' {
                     self.increment_box(chunks, cursor)?;
                 } else {
-                    self.boxes[self.index].content.push(c);
-                    self.boxes[self.index].width += c.width().unwrap_or(0)
-                }
-            }
-            KeyEvent::Backspace => {
-                let c = self.boxes[self.index].content.pop();
-                if let Some(c) = c {
-                    self.boxes[self.index].width -= c.width().unwrap_or(0)
+                    self.boxes[self.index].insert(c);
                 }
             }
-//            KeyEvent::Left => {
-//                cursor.move_left(1);
-//            },
-//            KeyEvent::Right => {
-//                cursor.move_right(1);
-//            },
+            KeyEvent::Backspace => self.boxes[self.index].delete_before(),
+            KeyEvent::Delete => self.boxes[self.index].delete_at(),
+            KeyEvent::Left => self.boxes[self.index].cursor_left(),
+            KeyEvent::Right => self.boxes[self.index].cursor_right(),
+            KeyEvent::Home => self.boxes[self.index].cursor_home(),
+            KeyEvent::End => self.boxes[self.index].cursor_end(),
             KeyEvent::Up => {
-                if self.boxes[self.index].scroll > 0 {
-                    self.boxes[self.index].scroll -= 1;
-//                    cursor.move_up(1);
+                let input_box = &mut self.boxes[self.index];
+                if input_box.cursor_up() {
+                    if let Some(chunk) = chunks.get(self.index) {
+                        input_box.scroll_to_cursor(*chunk);
+                    }
+                } else if input_box.scroll > 0 {
+                    input_box.scroll -= 1;
                 }
             }
             KeyEvent::Down => {
-                self.boxes[self.index].scroll += 1;
-//                cursor.move_down(1);
-            },
+                let input_box = &mut self.boxes[self.index];
+                if input_box.cursor_down() {
+                    if let Some(chunk) = chunks.get(self.index) {
+                        input_box.scroll_to_cursor(*chunk);
+                    }
+                } else {
+                    input_box.scroll += 1;
+                }
+            }
             KeyEvent::Esc => {
                 self.stop_writing();
                 return Ok((None, true));
@@ -267,18 +549,170 @@ impl InputBoxes {
         }
         Ok((None, false))
     }
+
+    /// Keys that `keypress` does something with, keep in sync
+    pub fn handles(key: &KeyEvent) -> bool {
+        match key {
+            KeyEvent::Ctrl(c) => *c == 's' || *c == 'w' || *c == 'n' || *c == 'b',
+            KeyEvent::Char(_)
+            | KeyEvent::Backspace
+            | KeyEvent::Delete
+            | KeyEvent::Left
+            | KeyEvent::Right
+            | KeyEvent::Home
+            | KeyEvent::End
+            | KeyEvent::Up
+            | KeyEvent::Down
+            | KeyEvent::Esc => true,
+            _ => false,
+        }
+    }
+}
+
+/// Splits the texts at byte `index` of their combined content and puts the fake cursor there
+/// `CursorStyle::Block` draws over the character at the index instead (unless it's a line break)
+fn insert_cursor(texts: Vec<Text>, index: usize) -> Vec<Text> {
+    let mut with_cursor = Vec::with_capacity(texts.len() + 2);
+    let mut start = 0;
+    let mut placed = false;
+    for styled_text in texts {
+        let (text, style) = match &styled_text {
+            Text::Styled(text, style) => (text.to_string(), Some(*style)),
+            Text::Raw(text) => (text.to_string(), None),
+        };
+        let end = start + text.len();
+        if placed || index < start || index >= end {
+            with_cursor.push(styled_text);
+            start = end;
+            continue;
+        }
+        let split = index - start;
+        let piece = |piece: &str| match style {
+            Some(style) => Text::Styled(piece.to_owned().into(), style),
+            None => Text::Raw(piece.to_owned().into()),
+        };
+        if split > 0 {
+            with_cursor.push(piece(&text[..split]));
+        }
+        let rest = match (CONFIG.cursor_style, text[split..].chars().next()) {
+            (CursorStyle::Block, Some(under)) if under != '\n' => {
+                with_cursor.push(utility::formatting::cursor(Some(under)));
+                &text[split + under.len_utf8()..]
+            }
+            _ => {
+                with_cursor.push(utility::formatting::cursor(None));
+                &text[split..]
+            }
+        };
+        if !rest.is_empty() {
+            with_cursor.push(piece(rest));
+        }
+        placed = true;
+        start = end;
+    }
+    if !placed {
+        with_cursor.push(utility::formatting::cursor(None));
+    }
+    with_cursor
+}
+
+/// What to do when a key doesn't do anything
+#[derive(Copy, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum UnknownKeyAction {
+    /// Nothing
+    Ignore,
+    /// Ring the terminal bell
+    Bell,
+    /// Say which key it was in the help box title
+    Message,
+}
+
+/// Rings the bell if `CONFIG.unknown_key` says so
+/// Returns a message to show if it's set to `Message`
+pub fn unknown_key(key: &KeyEvent) -> Result<Option<String>, Error> {
+    match CONFIG.unknown_key {
+        UnknownKeyAction::Ignore => Ok(None),
+        UnknownKeyAction::Bell => {
+            write!(io::stdout(), "\x07")?;
+            io::stdout().flush()?;
+            Ok(None)
+        }
+        UnknownKeyAction::Message => Ok(Some(format!("Nothing to do for {:?}", key))),
+    }
+}
+
+/// What `y <id>` copies
+#[derive(Copy, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CopyTarget {
+    /// The entry's ID
+    Id,
+    /// The path to the entry's file
+    Path,
+}
+
+/// A rectangle in the middle of `area`, taking up the given percentages of it
+pub fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Percentage((100 - percent_y) / 2),
+                Constraint::Percentage(percent_y),
+                Constraint::Percentage((100 - percent_y) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(area);
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Percentage((100 - percent_x) / 2),
+                Constraint::Percentage(percent_x),
+                Constraint::Percentage((100 - percent_x) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(vertical[1])[1]
+}
+
+/// Pads lines with spaces so they cover the inside of a bordered `area`
+/// (tui doesn't clear what's drawn underneath a widget)
+pub fn fill_area(lines: &[String], area: Rect) -> Vec<String> {
+    let width = area.width.saturating_sub(2) as usize;
+    let height = area.height.saturating_sub(2) as usize;
+    lines
+        .iter()
+        .map(String::as_str)
+        .chain(std::iter::repeat(""))
+        .take(height)
+        .map(|line| {
+            let line = line.chars().take(width).collect::<String>();
+            format!("{:width$}\n", line, width = width)
+        })
+        .collect()
 }
 
 /// Copied from `tui`/examples/util.rs
 pub enum Event<I> {
     Input(I),
     Tick,
+    /// an entry file was added, changed or removed (see `Events::watch`)
+    FileChanged(PathBuf),
 }
 
 /// A small event handler that wrap termion input and tick events. Each event
 /// type is handled in its own thread and returned to a common `Receiver`
+/// The input thread only reads the next key once `next` is called again,
+/// so nothing is read from the terminal while a key is being handled (e.g. while an editor runs)
 pub struct Events {
     rx: mpsc::Receiver<Event<KeyEvent>>,
+    /// for threads started later, like `watch`
+    tx: mpsc::Sender<Event<KeyEvent>>,
+    /// lets the input thread read the next key
+    resume_tx: mpsc::Sender<()>,
+    /// true => the last event was a key and the input thread is waiting for `resume_tx`
+    input_waiting: Cell<bool>,
     input_handle: thread::JoinHandle<()>,
     tick_handle: thread::JoinHandle<()>,
 }
@@ -294,6 +728,7 @@ impl Default for Events {
 impl Events {
     pub fn new(tick_rate: Duration) -> Events {
         let (tx, rx) = mpsc::channel();
+        let (resume_tx, resume_rx) = mpsc::channel();
         let input_handle = {
             let tx = tx.clone();
             thread::spawn(move || {
@@ -301,7 +736,7 @@ impl Events {
                 let reader = input.read_sync();
                 for evt in reader {
                     if let InputEvent::Keyboard(key) = evt {
-                        if tx.send(Event::Input(key.clone())).is_err() {
+                        if tx.send(Event::Input(key.clone())).is_err() || resume_rx.recv().is_err() {
                             return;
                         }
                     }
@@ -313,19 +748,228 @@ impl Events {
             thread::spawn(move || {
                 let tx = tx.clone();
                 loop {
-                    tx.send(Event::Tick).unwrap();
+                    if tx.send(Event::Tick).is_err() {
+                        return;
+                    }
                     thread::sleep(tick_rate);
                 }
             })
         };
         Events {
             rx,
+            tx,
+            resume_tx,
+            input_waiting: Cell::new(false),
             input_handle,
             tick_handle,
         }
     }
 
+    /// Checks the files matching the glob patterns every `interval` on another thread
+    /// and sends a `FileChanged` for each one that's new, changed (by modification time) or gone
+    /// Polls instead of using OS notifications, so it works the same everywhere
+    pub fn watch(&self, patterns: Vec<String>, interval: Duration) {
+        let tx = self.tx.clone();
+        thread::spawn(move || {
+            let mut known = file_times(&patterns);
+            loop {
+                thread::sleep(interval);
+                let current = file_times(&patterns);
+                let changed = current
+                    .iter()
+                    .filter(|(path, time)| known.get(*path) != Some(time))
+                    .map(|(path, _)| path)
+                    .chain(known.keys().filter(|path| !current.contains_key(*path)));
+                for path in changed {
+                    if tx.send(Event::FileChanged(path.clone())).is_err() {
+                        return;
+                    }
+                }
+                known = current;
+            }
+        });
+    }
+
     pub fn next(&self) -> Result<Event<KeyEvent>, mpsc::RecvError> {
-        self.rx.recv()
+        if self.input_waiting.replace(false) {
+            // the input thread is gone if this fails, then there's just no more input
+            self.resume_tx.send(()).ok();
+        }
+        let event = self.rx.recv()?;
+        if let Event::Input(_) = event {
+            self.input_waiting.set(true);
+        }
+        Ok(event)
+    }
+}
+
+/// Modification times of the files matching any of the glob patterns
+/// Files that can't be read are left out
+fn file_times(patterns: &[String]) -> HashMap<PathBuf, SystemTime> {
+    patterns
+        .iter()
+        .filter_map(|pattern| glob::glob(pattern).ok())
+        .flatten()
+        .filter_map(|path| path.ok())
+        .filter_map(|path| {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+            Some((path, modified))
+        })
+        .collect()
+}
+
+/// Hands the terminal back (main screen, no raw mode) so another program can use it
+pub fn suspend_screen() -> Result<(), Error> {
+    write!(io::stdout(), "\x1b[?1049l")?;
+    io::stdout().flush()?;
+    RawScreen::disable_raw_mode()?;
+    Ok(())
+}
+
+/// Takes the terminal over again after `suspend_screen`
+/// (the raw mode is undone when the original `AlternateScreen` is dropped)
+pub fn resume_screen() -> Result<(), Error> {
+    RawScreen::into_raw_mode()?.disable_drop();
+    write!(io::stdout(), "\x1b[?1049h")?;
+    io::stdout().flush()?;
+    Ok(())
+}
+
+/// Runs `$VISUAL` or `$EDITOR` (vi, or notepad on Windows, if neither is set) on a file
+/// and waits for it to close, it needs the terminal to itself (see `suspend_screen`)
+pub fn open_in_editor(path: &Path) -> Result<(), Error> {
+    let default = if cfg!(windows) { "notepad" } else { "vi" };
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| default.into());
+    // the setting can have arguments, e.g. "code --wait"
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or(default);
+    let status = Command::new(program).args(parts).arg(path).status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(Sorry::EditorFailed {
+            editor,
+            status: status.to_string(),
+        }
+        .into())
+    }
+}
+
+/// crossterm can't set the window title so these write the xterm escape codes directly
+/// Saves the current window title on the terminal's title stack
+pub fn push_terminal_title() -> Result<(), Error> {
+    write!(io::stdout(), "\x1b[22;0t")?;
+    Ok(io::stdout().flush()?)
+}
+
+/// Sets the window title
+pub fn set_terminal_title(title: &str) -> Result<(), Error> {
+    write!(io::stdout(), "\x1b]0;{}\x07", title)?;
+    Ok(io::stdout().flush()?)
+}
+
+/// Puts text on the clipboard with the OSC 52 escape code
+/// Works in most terminal emulators (and through ssh/tmux if they allow it) without a clipboard library
+pub fn copy_to_clipboard(text: &str) -> Result<(), Error> {
+    write!(io::stdout(), "\x1b]52;c;{}\x07", base64(text.as_bytes()))?;
+    Ok(io::stdout().flush()?)
+}
+
+/// Standard base64 with padding, all OSC 52 needs
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | u32::from(*b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Set when the terminal can't show its own cursor where typing goes, so a fake one is drawn
+static FAKE_CURSOR: AtomicBool = AtomicBool::new(false);
+
+/// true => input boxes draw `formatting::cursor` instead of using the terminal's cursor
+pub fn fake_cursor() -> bool {
+    FAKE_CURSOR.load(Ordering::Relaxed)
+}
+
+/// Switches to the fake cursor for the rest of the session
+pub fn use_fake_cursor() {
+    FAKE_CURSOR.store(true, Ordering::Relaxed);
+}
+
+/// Makes the terminal's cursor look like `style` (DECSCUSR, ignored by terminals that don't know it)
+pub fn set_cursor_shape(style: CursorStyle) -> Result<(), Error> {
+    let shape = match style {
+        CursorStyle::Block => 2,
+        CursorStyle::Underscore => 4,
+        CursorStyle::Bar => 6,
+    };
+    write!(io::stdout(), "\x1b[{} q", shape)?;
+    Ok(io::stdout().flush()?)
+}
+
+/// Puts the terminal's cursor back to the user's default look
+pub fn reset_cursor_shape() -> Result<(), Error> {
+    write!(io::stdout(), "\x1b[0 q")?;
+    Ok(io::stdout().flush()?)
+}
+
+/// Brings back the title saved by `push_terminal_title`
+pub fn pop_terminal_title() -> Result<(), Error> {
+    write!(io::stdout(), "\x1b[23;0t")?;
+    Ok(io::stdout().flush()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Markdown box with `content` and the cursor at byte `cursor`
+    fn input_box(content: &str, cursor: usize) -> InputBox {
+        let mut input_box = InputBox::new("Description".into(), true, 50);
+        input_box.content = content.into();
+        input_box.cursor = cursor;
+        input_box
+    }
+
+    #[test]
+    fn up_down_keep_the_column_across_shorter_lines() {
+        // cursor after "long li"
+        let mut input_box = input_box("a long line\nab\nanother line", 7);
+        assert!(input_box.cursor_down());
+        // "ab" is shorter, so the end of it
+        assert_eq!(input_box.cursor, 14);
+        assert!(input_box.cursor_down());
+        // back to column 7 on a line that's long enough
+        assert_eq!(&input_box.content[input_box.cursor..], " line");
+        assert!(!input_box.cursor_down());
+        assert!(input_box.cursor_up());
+        assert!(input_box.cursor_up());
+        assert_eq!(input_box.cursor, 7);
+        assert!(!input_box.cursor_up());
+        assert_eq!(input_box.cursor, 7);
+    }
+
+    #[test]
+    fn up_down_count_characters_not_bytes() {
+        // cursor after "né"
+        let mut input_box = input_box("née\nnew", 3);
+        assert!(input_box.cursor_down());
+        assert_eq!(&input_box.content[input_box.cursor..], "w");
+        assert!(input_box.cursor_up());
+        assert_eq!(input_box.cursor, 3);
     }
 }
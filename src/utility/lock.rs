@@ -0,0 +1,68 @@
+use std::{path::Path, process};
+
+use anyhow::Error;
+use path_abs::{PathDir, PathFile, PathOps};
+
+/// Lives in the entries folder while a gooseberry instance is using it
+pub const LOCK_FILE_NAME: &str = ".gooseberry.lock";
+
+/// Advisory lock on an entries folder, holds the PID of the instance using it
+/// Nothing stops a second instance from writing, it just gets a warning
+#[derive(Debug)]
+pub struct FolderLock {
+    file: PathFile,
+    /// false => another (running) instance has the lock, so don't remove it when done
+    owned: bool,
+}
+
+impl FolderLock {
+    /// Tries to take the lock on a folder
+    /// Returns a warning if another instance seems to have it or if a stale lock was replaced
+    pub fn acquire(folder: &PathDir) -> Result<(Self, Option<String>), Error> {
+        let lock_path = folder.join(LOCK_FILE_NAME);
+        let mut warning = None;
+        if lock_path.as_path().exists() {
+            let file = PathFile::new(&lock_path)?;
+            let other_pid = file.read_string()?.trim().parse::<u32>().ok();
+            match other_pid {
+                Some(pid) if pid != process::id() && process_running(pid) => {
+                    return Ok((
+                        Self { file, owned: false },
+                        Some(format!(
+                            "Another gooseberry (PID {}) is using this folder, changes may clash",
+                            pid
+                        )),
+                    ));
+                }
+                Some(pid) => warning = Some(format!("Replaced a stale lock from PID {}", pid)),
+                None => warning = Some("Replaced an unreadable lock file".into()),
+            }
+        }
+        let file = PathFile::create(&lock_path)?;
+        file.write_str(&process::id().to_string())?;
+        Ok((Self { file, owned: true }, warning))
+    }
+
+    /// false if another instance had the lock first
+    pub fn is_owned(&self) -> bool {
+        self.owned
+    }
+}
+
+impl Drop for FolderLock {
+    fn drop(&mut self) {
+        if self.owned {
+            self.file.clone().remove().ok();
+        }
+    }
+}
+
+/// Checks /proc for the PID, assumes it's running if there's no /proc to check
+fn process_running(pid: u32) -> bool {
+    let proc_dir = Path::new("/proc");
+    if proc_dir.is_dir() {
+        proc_dir.join(pid.to_string()).exists()
+    } else {
+        true
+    }
+}
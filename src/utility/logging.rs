@@ -0,0 +1,60 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    path::Path,
+    sync::Mutex,
+};
+
+use anyhow::Error;
+use chrono::Local;
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// Only gooseberry's own logs are written, not those of its dependencies
+const LOG_TARGET: &str = "gooseberry_tm";
+
+/// Appends log lines to a file, the terminal belongs to the TUI so nothing goes to stdout or stderr
+struct FileLogger {
+    file: Mutex<File>,
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.target().starts_with(LOG_TARGET)
+    }
+
+    /// e.g. `2026-10-15 11:40:02.153 INFO  gooseberry_tm::app: Saved Task 3`
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        if let Ok(mut file) = self.file.lock() {
+            // there's nowhere to say that a log line couldn't be written
+            writeln!(
+                file,
+                "{} {:<5} {}: {}",
+                Local::now().format("%F %T%.3f"),
+                record.level(),
+                record.target(),
+                record.args()
+            )
+            .ok();
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            file.flush().ok();
+        }
+    }
+}
+
+/// Starts logging to `path` (added to the end if it's already there), debug level and up
+/// Without this the log macros do nothing
+pub fn init(path: &Path) -> Result<(), Error> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    log::set_boxed_logger(Box::new(FileLogger {
+        file: Mutex::new(file),
+    }))?;
+    log::set_max_level(LevelFilter::Debug);
+    Ok(())
+}
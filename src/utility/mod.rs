@@ -1,3 +1,16 @@
 pub mod config;
 pub mod formatting;
 pub mod interactive;
+pub mod lock;
+pub mod logging;
+pub mod session;
+
+/// An empty folder in the temp dir for a test to write entries to, unique to the test name and the run
+#[cfg(test)]
+pub(crate) fn test_folder(name: &str) -> path_abs::PathDir {
+    let path = std::env::temp_dir().join(format!("gooseberry-{}-{}", name, std::process::id()));
+    if path.exists() {
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+    path_abs::PathDir::create_all(path).unwrap()
+}
@@ -0,0 +1,59 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Error;
+use directories::ProjectDirs;
+
+use crate::entry::GooseberryEntryType;
+use crate::utility::config::CONFIG_NAME;
+use crate::utility::formatting::DisplayMode;
+
+/// How a tab was left
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TabState {
+    pub entry_type: GooseberryEntryType,
+    pub display_mode: DisplayMode,
+}
+
+/// Where the user was when they quit, so the next start picks up there
+/// Kept apart from the entries, deleting the file just starts fresh
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    /// index of the active tab
+    pub active_tab: usize,
+    pub tabs: Vec<TabState>,
+}
+
+impl Session {
+    /// session.toml in the user's data folder
+    pub fn path() -> PathBuf {
+        ProjectDirs::from("rs", CONFIG_NAME, CONFIG_NAME)
+            .data_dir()
+            .join("session.toml")
+    }
+
+    /// The last session, or a fresh one if there's no session file or it can't be read
+    pub fn load() -> Self {
+        fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|session| toml::from_str(&session).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn store(&self) -> Result<(), Error> {
+        let path = Self::path();
+        if let Some(folder) = path.parent() {
+            fs::create_dir_all(folder)?;
+        }
+        fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// How the tab for `entry_type` was shown last time
+    pub fn display_mode(&self, entry_type: GooseberryEntryType) -> Option<DisplayMode> {
+        self.tabs
+            .iter()
+            .find(|t| t.entry_type == entry_type)
+            .map(|t| t.display_mode)
+    }
+}